@@ -0,0 +1,42 @@
+extern crate pack;
+
+use std::time::Instant;
+
+use pack::puzzle::bitmask;
+use pack::puzzle::solver::{solve, Target};
+use pack::puzzle::piece::{Position, Template};
+use pack::puzzle::pieces::Bag;
+
+/// Packs a 4×4×4 cube with twelve V-pentacubes and a square tetracube, once
+/// with the `Vec<Position>` backed `Target` and once with the `BitTarget`.
+fn main(){
+    let target = Target::cuboid(4, 4, 4);
+
+    let start = Instant::now();
+    let mut count: usize = 0;
+    solve(&target, bag(), &mut |_solution|{ count += 1 });
+    println!("Vec<Position>: {} solutions in {:?}", count, start.elapsed());
+
+    let start = Instant::now();
+    let mut count: usize = 0;
+    bitmask::solve(&target, bag(), &mut |_solution|{ count += 1 });
+    println!("BitTarget:     {} solutions in {:?}", count, start.elapsed());
+}
+
+fn bag() -> Bag<(i8, i8, i8)> {
+    Bag::exactly(vec!(
+        (12,Template::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, 0, 0),
+            Position::new(2, 0, 0),
+            Position::new(0, 1, 0),
+            Position::new(0, 2, 0),
+        ))),
+        (1,Template::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, 0, 0),
+            Position::new(0, 1, 0),
+            Position::new(1, 1, 0),
+        ))),
+    ))
+}
@@ -0,0 +1,67 @@
+extern crate pack;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use pack::puzzle::grid::Grid;
+use pack::puzzle::mutating::solve_mutating;
+use pack::puzzle::solver::{solve, Target};
+use pack::puzzle::piece::{Position, Template};
+use pack::puzzle::pieces::Bag;
+
+/// The system allocator, counting the number of allocations it makes.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn main(){
+    let start = Instant::now();
+    let mut count: usize = 0;
+    let immutable = allocations(|| solve(&target(), bag(), &mut |_solution|{ count += 1 }));
+    println!("immutable: {} solutions in {:?} with {} allocations", count, start.elapsed(), immutable);
+
+    let start = Instant::now();
+    let mut count: usize = 0;
+    let mutating = allocations(|| solve_mutating(Grid::from(&target()), bag(), &mut |_solution|{ count += 1 }));
+    println!("mutating:  {} solutions in {:?} with {} allocations", count, start.elapsed(), mutating);
+
+    assert!(mutating < immutable, "the mutating variant should allocate less");
+    println!("the mutating variant makes {:.1} times fewer allocations", immutable as f64 / mutating as f64);
+}
+
+/// The number of allocations made while running `f`.
+fn allocations<F>(f: F) -> usize where F: FnOnce() {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    f();
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
+fn target() -> Target<(i8, i8, i8)> {
+    Target::cuboid(4, 4, 2)
+}
+
+fn bag() -> Bag<(i8, i8, i8)> {
+    Bag::new(vec!(
+        (8,Template::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, 0, 0),
+            Position::new(0, 1, 0),
+            Position::new(2, 0, 0),
+        ))),
+    ))
+}
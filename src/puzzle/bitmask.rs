@@ -0,0 +1,227 @@
+//! Bitmask representation of a `Target` for fast packing.
+use super::piece::{bounding_box, CubeSymmetry, Piece, Position, Positionable, MinimumPosition, Translatable};
+use super::pieces::{Bag, Bound};
+use super::solver::{Solution, Target};
+
+const BITS: usize = 64;
+
+/// The orientations of a `Template`, with the `CubeSymmetry` that produced each.
+type Orientations = Vec<(CubeSymmetry, Piece<(i8, i8, i8)>)>;
+
+/// A `Target` that fits in a bounded box, stored as one bit per cell of the
+/// box. Checking if a `Piece` fits is an AND of its mask with the open cells,
+/// placing it clears the bits of the mask.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct BitTarget {
+    origin: (i8, i8, i8),
+    dimensions: (usize, usize, usize),
+    words: Vec<u64>,
+}
+
+impl BitTarget {
+    /// Create a `BitTarget` from the smallest box that contains the `Target`.
+    pub fn from_target(target: &Target<(i8, i8, i8)>) -> BitTarget {
+        let (origin, dimensions) = match bounding_box(target.positions()) {
+            Some((minimum, maximum)) => {
+                let (min_x, min_y, min_z) = *minimum.coordinates();
+                let (max_x, max_y, max_z) = *maximum.coordinates();
+                let dimensions = (
+                    (max_x as isize - min_x as isize + 1) as usize,
+                    (max_y as isize - min_y as isize + 1) as usize,
+                    (max_z as isize - min_z as isize + 1) as usize,
+                );
+                ((min_x, min_y, min_z), dimensions)
+            }
+            None => ((0, 0, 0), (0, 0, 0)),
+        };
+        let (width, height, depth) = dimensions;
+        let mut bit_target = BitTarget {
+            origin,
+            dimensions,
+            words: vec!(0; (width * height * depth).div_ceil(BITS)),
+        };
+        for position in target.positions() {
+            let index = bit_target.index(position).unwrap();
+            bit_target.words[index / BITS] |= 1 << (index % BITS);
+        }
+        bit_target
+    }
+
+    /// The number of cells that are still open.
+    pub fn volume(&self) -> usize {
+        self.words.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    /// Determine if there is nothing left to pack.
+    pub fn is_packed(&self) -> bool {
+        self.words.iter().all(|word| *word == 0)
+    }
+
+    /// Determine if a `Position` is still open.
+    pub fn contains(&self, position: &Position<(i8, i8, i8)>) -> bool {
+        match self.index(position) {
+            Some(index) => self.words[index / BITS] & (1 << (index % BITS)) != 0,
+            None => false,
+        }
+    }
+
+    /// The mask of the cells a collection of `Position`s occupies. `None` when a
+    /// `Position` lies outside the box of this `BitTarget`.
+    pub fn mask<I>(&self, positions: I) -> Option<Vec<u64>> where I: IntoIterator<Item = Position<(i8, i8, i8)>> {
+        let mut mask: Vec<u64> = vec!(0; self.words.len());
+        for position in positions {
+            let index = self.index(&position)?;
+            mask[index / BITS] |= 1 << (index % BITS);
+        }
+        Some(mask)
+    }
+
+    /// Determine if all cells of `mask` are open.
+    pub fn fits(&self, mask: &[u64]) -> bool {
+        self.words.iter().zip(mask).all(|(word, bits)| word & bits == *bits)
+    }
+
+    /// Close the cells of `mask`. *Note* caller is responsible to check if the
+    /// mask actually fits.
+    pub fn place(&self, mask: &[u64]) -> BitTarget {
+        let words: Vec<u64> = self.words.iter().zip(mask).map(|(word, bits)| word & !bits).collect();
+        BitTarget { words, ..*self }
+    }
+
+    /// The index of `position` in the box, ordered like `Position`s are, so the
+    /// first open bit is the minimum open `Position`.
+    fn index(&self, position: &Position<(i8, i8, i8)>) -> Option<usize> {
+        let (x, y, z) = *position.coordinates();
+        let (width, height, depth) = self.dimensions;
+        let offset = |coordinate: i8, origin: i8, size: usize| {
+            let offset = coordinate as isize - origin as isize;
+            if 0 <= offset && (offset as usize) < size { Some(offset as usize) } else { None }
+        };
+        let dx = offset(x, self.origin.0, width)?;
+        let dy = offset(y, self.origin.1, height)?;
+        let dz = offset(z, self.origin.2, depth)?;
+        Some((dx * height + dy) * depth + dz)
+    }
+
+    fn position(&self, index: usize) -> Position<(i8, i8, i8)> {
+        let (_, height, depth) = self.dimensions;
+        let dz = index % depth;
+        let dy = (index / depth) % height;
+        let dx = index / (depth * height);
+        Position::new(
+            (self.origin.0 as isize + dx as isize) as i8,
+            (self.origin.1 as isize + dy as isize) as i8,
+            (self.origin.2 as isize + dz as isize) as i8,
+        )
+    }
+}
+
+impl MinimumPosition<(i8, i8, i8)> for BitTarget {
+    fn minimum_position(&self) -> Option<Position<(i8, i8, i8)>> {
+        self.words
+            .iter()
+            .enumerate()
+            .find(|(_, word)| **word != 0)
+            .map(|(index, word)| self.position(index * BITS + word.trailing_zeros() as usize))
+    }
+}
+
+/// Variant of the `solve` method that keeps track of the open cells in a
+/// `BitTarget`, and that determines the orientations of every `Template` once,
+/// up front. It finds the same solutions as `solve`, though not necessarily in
+/// the same order, but it is a lot faster for larger puzzles.
+pub fn solve<F>(target: &Target<(i8, i8, i8)>, bag: Bag<(i8, i8, i8)>, when_solved: &mut F) where F: (FnMut(Solution<(i8, i8, i8)>)) + Sized {
+    let bit_target = BitTarget::from_target(target);
+    let (bounds, orientations): (Vec<Bound>, Vec<Orientations>) = bag
+        .into_entries()
+        .map(|(bound, template)| (bound, template.into_iter().collect_with_symmetries()))
+        .unzip();
+    let mut counts: Vec<usize> = bounds.iter().map(Bound::count).collect();
+    let optional: Vec<bool> = bounds.iter().map(|bound| matches!(bound, Bound::UpTo(_))).collect();
+    solve_with(&bit_target, &orientations, &mut counts, &optional, Solution::empty(), when_solved)
+}
+
+fn solve_with<F>(target: &BitTarget, orientations: &[Orientations], counts: &mut [usize], optional: &[bool], partial_solution: Solution<(i8, i8, i8)>, when_solved: &mut F) where F: (FnMut(Solution<(i8, i8, i8)>)) + Sized {
+    if target.is_packed() {
+        if counts.iter().zip(optional).all(|(count, optional)| *count == 0 || *optional) {
+            when_solved(partial_solution)
+        }
+    } else {
+        let open_position = target.minimum_position().unwrap();
+        for index in 0..counts.len() {
+            if counts[index] == 0 {
+                continue;
+            }
+            counts[index] -= 1;
+            for (symmetry, orientation) in &orientations[index] {
+                let mut piece = orientation.clone();
+                let block = piece.minimum_position().unwrap();
+                piece.translate(&block.to(&open_position));
+                if let Some(mask) = target.mask(piece.iter()) {
+                    if target.fits(&mask) {
+                        let remaining_target = target.place(&mask);
+                        let candidate_solution = partial_solution.record_with_symmetry(&piece, Some(*symmetry));
+                        solve_with(&remaining_target, orientations, counts, optional, candidate_solution, when_solved)
+                    }
+                }
+            }
+            counts[index] += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::piece::Template;
+    use super::super::solver;
+    use super::*;
+
+    #[test]
+    fn bit_target_should_track_open_cells() {
+        let target = Target::new(vec!(
+            Position::new(1, 0, 0),
+            Position::new(1, 1, 0),
+            Position::new(2, 1, 0),
+            Position::new(1, 1, 1),
+        ));
+
+        let bit_target = BitTarget::from_target(&target);
+        let mask = bit_target.mask(vec!(Position::new(1, 1, 0), Position::new(1, 0, 0))).expect("inside box");
+        let remaining = bit_target.place(&mask);
+
+        assert_eq!(bit_target.volume(), 4);
+        assert_eq!(bit_target.minimum_position(), Some(Position::new(1, 0, 0)));
+        assert!(bit_target.fits(&mask));
+        assert!(!bit_target.contains(&Position::new(2, 0, 0)));
+        assert_eq!(remaining.volume(), 2);
+        assert!(!remaining.fits(&mask));
+        assert_eq!(remaining.minimum_position(), Some(Position::new(1, 1, 1)));
+        assert_eq!(bit_target.mask(vec!(Position::new(0, 0, 0))), None);
+    }
+
+    #[test]
+    fn solve_should_find_the_same_solutions() {
+        let target = Target::cuboid(3, 3, 3);
+        let bag = Bag::new(vec!(
+            (6,Template::new(vec!(
+                Position::new(0, 0, 0),
+                Position::new(1, 0, 0),
+                Position::new(0, 1, 0),
+                Position::new(1, 1, 0),
+            ))),
+            (3,Template::new(vec!(
+                Position::new(0, 0, 0),
+            ))),
+        ));
+
+        let mut expected: Vec<String> = vec!();
+        solver::solve(&target, bag.clone(), &mut |solution| expected.push(format!("{}", solution)));
+        let mut actual: Vec<String> = vec!();
+        solve(&target, bag, &mut |solution| actual.push(format!("{}", solution)));
+        expected.sort();
+        actual.sort();
+
+        assert!(!expected.is_empty());
+        assert_eq!(actual, expected);
+    }
+}
@@ -0,0 +1,187 @@
+//! A fluent way of describing and solving packing problems.
+
+use std::fmt::{Display, Formatter, Error};
+
+use super::piece::Template;
+use super::pieces::{Bag, Bound};
+use super::solver::{solve, DimensionError, Solution, Target};
+
+/// Reasons why a puzzle described by a `PuzzleBuilder` can not be solved.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum PuzzleError {
+    /// No target region was described.
+    MissingTarget,
+    /// The target region has no cells.
+    EmptyTarget,
+    /// No pieces were added.
+    NoPieces,
+    /// A piece without cells was added.
+    EmptyPiece,
+    /// The pieces can not fill the target exactly.
+    VolumeMismatch {
+        /// Number of cells of the target.
+        target: usize,
+        /// Number of cells of all the pieces together.
+        pieces: usize,
+    },
+    /// The dimensions of the target are not valid.
+    Dimension(DimensionError),
+}
+
+impl Display for PuzzleError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        match *self {
+            PuzzleError::MissingTarget => write!(f, "no target was described"),
+            PuzzleError::EmptyTarget => write!(f, "the target has no cells"),
+            PuzzleError::NoPieces => write!(f, "no pieces were added"),
+            PuzzleError::EmptyPiece => write!(f, "a piece has no cells"),
+            PuzzleError::VolumeMismatch { target, pieces } => write!(f, "pieces cover {} cells but the target has {}", pieces, target),
+            PuzzleError::Dimension(ref error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for PuzzleError {}
+
+impl From<DimensionError> for PuzzleError {
+    fn from(error: DimensionError) -> Self {
+        PuzzleError::Dimension(error)
+    }
+}
+
+type Pieces = Vec<(usize, Template<(i8, i8, i8)>)>;
+
+/// Describe a packing problem step by step and solve it. The puzzle is checked
+/// for consistency before the solver is started.
+#[derive(Debug, Clone)]
+pub struct PuzzleBuilder {
+    dimensions: Option<(usize, usize, usize)>,
+    pieces: Pieces,
+    allow_reflections: bool,
+    require_all: bool,
+}
+
+impl PuzzleBuilder {
+    /// Start describing a puzzle. By default reflections are not allowed and
+    /// all pieces need to be used.
+    pub fn new() -> PuzzleBuilder {
+        PuzzleBuilder { dimensions: None, pieces: vec!(), allow_reflections: false, require_all: true }
+    }
+
+    /// Pack a brick with the given dimensions.
+    pub fn brick(self, width: usize, height: usize, depth: usize) -> PuzzleBuilder {
+        PuzzleBuilder { dimensions: Some((width, height, depth)), ..self }
+    }
+
+    /// Add `count` copies of a piece.
+    pub fn add_piece(mut self, template: Template<(i8, i8, i8)>, count: usize) -> PuzzleBuilder {
+        self.pieces.push((count, template));
+        self
+    }
+
+    /// Determine if pieces may be flipped over into their mirror image.
+    pub fn allow_reflections(self, allow_reflections: bool) -> PuzzleBuilder {
+        PuzzleBuilder { allow_reflections, ..self }
+    }
+
+    /// Determine if all the pieces need to be used, or if the target can be
+    /// packed with a selection of them.
+    pub fn require_all(self, require_all: bool) -> PuzzleBuilder {
+        PuzzleBuilder { require_all, ..self }
+    }
+
+    /// Check the puzzle for consistency and solve it. When a solution is found,
+    /// the `when_solved` callback is called with that solution.
+    pub fn solve<F>(self, when_solved: &mut F) -> Result<(), PuzzleError> where F: FnMut(Solution<(i8, i8, i8)>) {
+        let (width, height, depth) = self.dimensions.ok_or(PuzzleError::MissingTarget)?;
+        let target = Target::try_from_box(width, height, depth)?;
+        let target_volume = target.volume();
+        if target_volume == 0 {
+            return Err(PuzzleError::EmptyTarget);
+        }
+        if self.pieces.iter().all(|(count, _)| *count == 0) {
+            return Err(PuzzleError::NoPieces);
+        }
+        if self.pieces.iter().any(|(_, template)| template.is_empty()) {
+            return Err(PuzzleError::EmptyPiece);
+        }
+        let allow_reflections = self.allow_reflections;
+        let require_all = self.require_all;
+        let collection = self.pieces
+            .into_iter()
+            .filter(|(count, _)| *count > 0)
+            .map(|(count, template)| if allow_reflections { (count, template.with_reflections()) } else { (count, template) })
+            .map(|(count, template)| if require_all { (Bound::Exactly(count), template) } else { (Bound::UpTo(count), template) })
+            .collect();
+        let bag = Bag::new_bounded(collection);
+        let pieces_volume = bag.total_volume();
+        if pieces_volume < target_volume || (self.require_all && pieces_volume != target_volume) {
+            return Err(PuzzleError::VolumeMismatch { target: target_volume, pieces: pieces_volume });
+        }
+
+        solve(&target, bag, when_solved);
+        Ok(())
+    }
+}
+
+impl Default for PuzzleBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::piece::Position;
+    use super::*;
+
+    fn tripod() -> Template<(i8, i8, i8)> {
+        Template::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, 0, 0),
+            Position::new(0, 1, 0),
+            Position::new(0, 0, 1),
+        ))
+    }
+
+    #[test]
+    fn builder_should_solve_two_by_two_by_two() {
+        let mut solutions: Vec<Solution<(i8, i8, i8)>> = vec!();
+
+        let result = PuzzleBuilder::new()
+            .brick(2, 2, 2)
+            .add_piece(tripod(), 2)
+            .solve(&mut |solution| solutions.push(solution));
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(solutions.len(), 4);
+    }
+
+    #[test]
+    fn builder_should_allow_unused_pieces_when_not_all_required() {
+        let mut count = 0;
+
+        let result = PuzzleBuilder::new()
+            .brick(2, 2, 2)
+            .add_piece(tripod(), 3)
+            .require_all(false)
+            .solve(&mut |_| count += 1);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(count, 4);
+    }
+
+    #[test]
+    fn builder_should_report_inconsistent_puzzles() {
+        let mut count = 0;
+
+        assert_eq!(PuzzleBuilder::new().add_piece(tripod(), 2).solve(&mut |_| count += 1), Err(PuzzleError::MissingTarget));
+        assert_eq!(PuzzleBuilder::new().brick(2, 0, 2).add_piece(tripod(), 2).solve(&mut |_| count += 1), Err(PuzzleError::EmptyTarget));
+        assert_eq!(PuzzleBuilder::new().brick(2, 2, 2).solve(&mut |_| count += 1), Err(PuzzleError::NoPieces));
+        assert_eq!(PuzzleBuilder::new().brick(2, 2, 2).add_piece(Template::new(vec!()), 1).solve(&mut |_| count += 1), Err(PuzzleError::EmptyPiece));
+        assert_eq!(
+            PuzzleBuilder::new().brick(2, 2, 2).add_piece(tripod(), 3).solve(&mut |_| count += 1),
+            Err(PuzzleError::VolumeMismatch { target: 8, pieces: 12 }));
+        assert_eq!(count, 0);
+    }
+}
@@ -0,0 +1,178 @@
+//! Exact cover formulation of packing problems, solved with Dancing Links.
+use std::collections::HashMap;
+
+use ::dlx::{Row, Solver, Solutions};
+
+use super::vector::{VectorAdd, VectorDifference};
+use super::piece::{MinimumPosition, Normalizable, Piece, Position, Transformable};
+use super::pieces::{Bag, Bound};
+use super::solver::{Solution, Target};
+
+/// A placement of a copy of a `Template`, indexed by its row in the matrix.
+struct Placement<T> {
+    template: usize,
+    copy: usize,
+    piece: Piece<T>,
+}
+
+/// Variant of the `solve` method that casts the packing problem as an exact
+/// cover problem and solves it with Algorithm X.
+///
+/// Every placement of every `Template` is determined once, up front. The matrix
+/// has a column for every copy of every `Template` and a column for every cell
+/// of the `Target`, and a row for every placement of every copy. A copy with a
+/// `Bound::UpTo` also has a row that leaves it unused, which keeps every later
+/// copy of its `Template` unused as well.
+///
+/// Identical copies of a `Template` are forced into the order of the first cell
+/// they cover by the matrix itself. For every cell there is an ordering column
+/// between consecutive copies. A copy covers the ordering columns of the cells
+/// from its first cell on with the copy before it, and those up to its first
+/// cell with the copy after it, so two copies only fit in order. An ordering
+/// column that is not covered this way is covered by a row of its own. Every
+/// packing is found exactly once.
+pub fn solve_exact_cover<F, T>(target: &Target<T>, bag: Bag<T>, when_solved: &mut F) where F: (FnMut(Solution<T>)) + Sized, T: Clone + PartialOrd + Ord + Transformable + Normalizable<T> + VectorDifference<T> + VectorAdd<T> {
+    let cells: &[Position<T>] = target.positions();
+    let entries: Vec<(Bound, _)> = bag.into_entries().collect();
+    let copies: usize = entries.iter().map(|(bound, _)| bound.count()).sum();
+    let index = |position: &Position<T>| cells.iter().position(|cell| cell == position).unwrap();
+    let mut column: usize = 0;
+    let mut columns: usize = copies + cells.len();
+    let mut rows: Vec<Row> = vec!();
+    let mut placements: HashMap<Row, Placement<T>> = HashMap::new();
+    for (template_index, (bound, template)) in entries.into_iter().enumerate() {
+        let count = bound.count();
+        let pieces: Vec<Piece<T>> = target.placements(&template);
+        let orders = columns;
+        let ordering = |copy: usize, cell: usize| orders + (copy - 1) * cells.len() + cell;
+        for copy in 0..count {
+            for piece in &pieces {
+                let first = index(&piece.minimum_position().unwrap());
+                let mut row: Row = vec!(column);
+                row.extend(piece.iter().map(|position| copies + index(&position)));
+                if copy > 0 {
+                    row.extend((first..cells.len()).map(|cell| ordering(copy, cell)));
+                }
+                if copy + 1 < count {
+                    row.extend((0..=first).map(|cell| ordering(copy + 1, cell)));
+                }
+                rows.push(row.clone());
+                placements.insert(row, Placement { template: template_index, copy, piece: piece.clone() });
+            }
+            if bound.is_optional() {
+                let mut row: Row = vec!(column);
+                if copy + 1 < count {
+                    row.extend((0..cells.len()).map(|cell| ordering(copy + 1, cell)));
+                }
+                rows.push(row);
+            }
+            column += 1;
+        }
+        for copy in 1..count {
+            rows.extend((0..cells.len()).map(|cell| vec!(ordering(copy, cell))));
+        }
+        columns += count.saturating_sub(1) * cells.len();
+    }
+
+    let mut solver = Solver::new(columns, rows.into_iter());
+    let mut collector = Collector { placements: &placements, when_solved };
+    solver.solve(vec!(), &mut collector);
+}
+
+/// Hands the placements of every exact cover to the callback as a `Solution`.
+struct Collector<'a, F, T> {
+    placements: &'a HashMap<Row, Placement<T>>,
+    when_solved: &'a mut F,
+}
+
+impl<'a, F, T> Solutions for Collector<'a, F, T> where F: FnMut(Solution<T>), T: Clone + PartialOrd + Ord {
+    fn push(&mut self, rows: ::dlx::Solution) -> bool {
+        let mut chosen: Vec<&Placement<T>> = rows.filter_map(|row| self.placements.get(&row)).collect();
+        chosen.sort_by_key(|placement| (placement.template, placement.copy));
+        let solution = chosen.iter().fold(Solution::empty(), |solution, placement| solution.record(&placement.piece));
+        (self.when_solved)(solution);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::piece::Template;
+    use super::super::solver::count_solutions;
+    use super::*;
+
+    #[test]
+    fn solve_exact_cover_should_find_all_packings() {
+        let target = Target::cuboid(2, 2, 2);
+        let bag = Bag::new(vec!(
+            (2,Template::new(vec!(
+                Position::new(0, 0, 0),
+                Position::new(1, 0, 0),
+                Position::new(0, 1, 0),
+                Position::new(0, 0, 1),
+            ))),
+        ));
+
+        let mut count: usize = 0;
+        solve_exact_cover(&target, bag.clone(), &mut |_|{ count += 1 });
+
+        assert_eq!(count, count_solutions(&target, bag));
+    }
+
+    #[test]
+    fn solve_exact_cover_should_report_identical_copies_once() {
+        let target = Target::cuboid(2, 2, 2);
+        let bag = Bag::new(vec!(
+            (8,Template::new(vec!(
+                Position::new(0, 0, 0),
+            ))),
+        ));
+
+        let mut solutions: Vec<Solution<(i8, i8, i8)>> = vec!();
+        solve_exact_cover(&target, bag, &mut |solution| solutions.push(solution));
+
+        assert_eq!(solutions.len(), 1);
+        assert_eq!(solutions[0].len(), 8);
+    }
+
+    #[test]
+    fn solve_exact_cover_should_agree_with_solve() {
+        let target = Target::cuboid(3, 3, 3);
+        let bag = Bag::new(vec!(
+            (6,Template::new(vec!(
+                Position::new(0, 0, 0),
+                Position::new(1, 0, 0),
+                Position::new(0, 1, 0),
+                Position::new(1, 1, 0),
+            ))),
+            (3,Template::new(vec!(
+                Position::new(0, 0, 0),
+            ))),
+        ));
+
+        let mut count: usize = 0;
+        solve_exact_cover(&target, bag.clone(), &mut |_|{ count += 1 });
+
+        assert_eq!(count, count_solutions(&target, bag));
+    }
+
+    #[test]
+    fn solve_exact_cover_should_leave_optional_copies_unused() {
+        let target = Target::cuboid(2, 2, 1);
+        let bag = Bag::new_bounded(vec!(
+            (Bound::UpTo(3),Template::new(vec!(
+                Position::new(0, 0, 0),
+                Position::new(1, 0, 0),
+            ))),
+            (Bound::UpTo(4),Template::new(vec!(
+                Position::new(0, 0, 0),
+            ))),
+        ));
+
+        let mut count: usize = 0;
+        solve_exact_cover(&target, bag.clone(), &mut |_|{ count += 1 });
+
+        assert_eq!(count, count_solutions(&target, bag));
+        assert!(count > 0);
+    }
+}
@@ -0,0 +1,314 @@
+//! Exporting solutions to formats other tools understand.
+use std::fmt::{Display, Write};
+
+use super::piece::{bounding_box, Piece, Position, Rgb};
+use super::solver::Solution;
+
+/// The colors given to the `Piece`s that do not have a color of their own.
+const PALETTE: [Rgb; 8] = [
+    Rgb { red: 0xe6, green: 0x19, blue: 0x4b },
+    Rgb { red: 0x3c, green: 0xb4, blue: 0x4b },
+    Rgb { red: 0xff, green: 0xe1, blue: 0x19 },
+    Rgb { red: 0x43, green: 0x63, blue: 0xd8 },
+    Rgb { red: 0xf5, green: 0x82, blue: 0x31 },
+    Rgb { red: 0x91, green: 0x1e, blue: 0xb4 },
+    Rgb { red: 0x46, green: 0xf0, blue: 0xf0 },
+    Rgb { red: 0xf0, green: 0x32, blue: 0xe6 },
+];
+
+/// The color of the `Piece` at `index` in a `Solution`, or else a color from
+/// the `PALETTE`.
+fn piece_color<T>(index: usize, piece: &Piece<T>) -> Rgb where T: Clone + Ord {
+    piece.color().unwrap_or(PALETTE[index % PALETTE.len()])
+}
+
+impl Solution<(i8, i8)> {
+    /// Draw the `Solution` as an SVG image, with squares of `cell_px` pixels
+    /// per cell. The image is as large as the bounding box of the `Solution`.
+    /// Every `Piece` is a group of squares, filled with the color of the `Piece`
+    /// or else with a color from a fixed palette. The group has the id `pieceN`,
+    /// where `N` is the index of the `Piece` in the `Solution`, and carries the
+    /// name of the `Piece`, if it has one, in a `data-name` attribute. Copies of
+    /// a `Template` share their name, so the name can not serve as the id.
+    pub fn to_svg(&self, cell_px: u32) -> String {
+        let named_pieces = self.named_pieces();
+        let positions: Vec<Position<(i8, i8)>> = named_pieces.iter().flat_map(|(_, piece)| piece.iter()).collect();
+        let (minimum, maximum) = bounding_box(&positions).unwrap_or((Position::d2(0, 0), Position::d2(-1, -1)));
+        let (min_x, min_y) = *minimum.coordinates();
+        let (max_x, max_y) = *maximum.coordinates();
+        let width = (i32::from(max_x) - i32::from(min_x) + 1) as u32 * cell_px;
+        let height = (i32::from(max_y) - i32::from(min_y) + 1) as u32 * cell_px;
+
+        let mut svg = String::new();
+        writeln!(svg, "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\">", width, height).unwrap();
+        for (index, (name, piece)) in named_pieces.into_iter().enumerate() {
+            let color = piece_color(index, piece);
+            write!(svg, "<g id=\"piece{}\"", index).unwrap();
+            if let Some(name) = name {
+                svg.push_str(" data-name=\"");
+                write_xml_escaped(&mut svg, name);
+                svg.push('"');
+            }
+            writeln!(svg, " fill=\"{}\">", color).unwrap();
+            for position in piece.iter() {
+                let (x, y) = *position.coordinates();
+                writeln!(
+                    svg,
+                    "<rect x=\"{}\" y=\"{}\" width=\"{2}\" height=\"{2}\"/>",
+                    (i32::from(x) - i32::from(min_x)) as u32 * cell_px,
+                    (i32::from(y) - i32::from(min_y)) as u32 * cell_px,
+                    cell_px,
+                ).unwrap();
+            }
+            svg.push_str("</g>\n");
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+/// Write `value` as XML attribute text, escaping where necessary.
+fn write_xml_escaped(xml: &mut String, value: &str) {
+    for character in value.chars() {
+        match character {
+            '&' => xml.push_str("&amp;"),
+            '<' => xml.push_str("&lt;"),
+            '>' => xml.push_str("&gt;"),
+            '"' => xml.push_str("&quot;"),
+            character => xml.push(character),
+        }
+    }
+}
+
+impl Solution<(i8, i8, i8)> {
+    /// Describe the `Solution` as a [Wavefront OBJ](https://en.wikipedia.org/wiki/Wavefront_.obj_file)
+    /// mesh. Every cell becomes a cube with sides of `cube_size`, and the cubes
+    /// of a `Piece` are grouped in an object named after the `Piece`. Every
+    /// object uses the material of its color, which is the color of the `Piece`
+    /// or else one from the same palette as `to_svg`. The materials are defined
+    /// by `to_mtl`. *Note* faces shared by neighbouring cells are not culled.
+    pub fn to_obj(&self, cube_size: f32) -> String {
+        const CORNERS: [(i8, i8, i8); 8] = [
+            (0, 0, 0), (1, 0, 0), (1, 1, 0), (0, 1, 0),
+            (0, 0, 1), (1, 0, 1), (1, 1, 1), (0, 1, 1),
+        ];
+        const FACES: [[usize; 4]; 6] = [
+            [0, 3, 2, 1], [4, 5, 6, 7],
+            [0, 1, 5, 4], [2, 3, 7, 6],
+            [0, 4, 7, 3], [1, 2, 6, 5],
+        ];
+        let mut obj = String::new();
+        let mut vertices: usize = 0;
+        for (index, (name, piece)) in self.named_pieces().into_iter().enumerate() {
+            match name {
+                Some(name) => writeln!(obj, "o {}", name).unwrap(),
+                None => writeln!(obj, "o piece{}", index).unwrap(),
+            }
+            writeln!(obj, "usemtl {}", material_name(piece_color(index, piece))).unwrap();
+            for position in piece.iter() {
+                let (x, y, z) = *position.coordinates();
+                for (dx, dy, dz) in &CORNERS {
+                    writeln!(
+                        obj,
+                        "v {} {} {}",
+                        f32::from(i16::from(x) + i16::from(*dx)) * cube_size,
+                        f32::from(i16::from(y) + i16::from(*dy)) * cube_size,
+                        f32::from(i16::from(z) + i16::from(*dz)) * cube_size,
+                    ).unwrap();
+                }
+                for face in &FACES {
+                    writeln!(
+                        obj,
+                        "f {} {} {} {}",
+                        vertices + face[0] + 1,
+                        vertices + face[1] + 1,
+                        vertices + face[2] + 1,
+                        vertices + face[3] + 1,
+                    ).unwrap();
+                }
+                vertices += CORNERS.len();
+            }
+        }
+        obj
+    }
+
+    /// Describe the materials that `to_obj` uses as a Wavefront MTL material
+    /// library, with a diffuse color per material.
+    pub fn to_mtl(&self) -> String {
+        let mut colors: Vec<Rgb> = vec!();
+        for (index, (_, piece)) in self.named_pieces().into_iter().enumerate() {
+            let color = piece_color(index, piece);
+            if !colors.contains(&color) {
+                colors.push(color);
+            }
+        }
+        let mut mtl = String::new();
+        for color in colors {
+            writeln!(mtl, "newmtl {}", material_name(color)).unwrap();
+            writeln!(
+                mtl,
+                "Kd {} {} {}",
+                f32::from(color.red) / 255.0,
+                f32::from(color.green) / 255.0,
+                f32::from(color.blue) / 255.0,
+            ).unwrap();
+        }
+        mtl
+    }
+}
+
+/// The name of the OBJ material of a color.
+fn material_name(color: Rgb) -> String {
+    format!("color_{:02x}{:02x}{:02x}", color.red, color.green, color.blue)
+}
+
+impl<N> Solution<(N, N, N)> where N: Display + Ord + Clone {
+    /// Describe the `Solution` as JSON. The schema is an array with an object
+    /// per `Piece`. Each object has a `name`, which is `null` for an anonymous
+    /// `Piece`, a `color`, which is a `"#rrggbb"` string or `null` for a `Piece`
+    /// without a color, and `positions`, an array of `[x, y, z]` integer triples.
+    ///
+    /// ```json
+    /// [{"name":"A","color":"#ff0000","positions":[[0,0,0],[1,0,0]]},{"name":null,"color":null,"positions":[[0,1,0]]}]
+    /// ```
+    pub fn to_json(&self) -> String {
+        let mut json = String::from("[");
+        for (index, (name, piece)) in self.named_pieces().into_iter().enumerate() {
+            if index > 0 {
+                json.push(',');
+            }
+            json.push_str("{\"name\":");
+            match name {
+                Some(name) => write_json_string(&mut json, name),
+                None => json.push_str("null"),
+            }
+            json.push_str(",\"color\":");
+            match piece.color() {
+                Some(color) => write!(json, "\"{}\"", color).unwrap(),
+                None => json.push_str("null"),
+            }
+            json.push_str(",\"positions\":[");
+            for (index, position) in piece.iter().enumerate() {
+                if index > 0 {
+                    json.push(',');
+                }
+                let (x, y, z) = position.coordinates();
+                write!(json, "[{},{},{}]", x, y, z).unwrap();
+            }
+            json.push_str("]}");
+        }
+        json.push(']');
+        json
+    }
+}
+
+/// Write `value` as a JSON string literal, escaping where necessary.
+fn write_json_string(json: &mut String, value: &str) {
+    json.push('"');
+    for character in value.chars() {
+        match character {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            character if (character as u32) < 0x20 => write!(json, "\\u{:04x}", character as u32).unwrap(),
+            character => json.push(character),
+        }
+    }
+    json.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::piece::Piece;
+    use super::*;
+
+    #[test]
+    fn to_svg_should_draw_a_rect_per_cell() {
+        let solution =
+            Solution::empty()
+            .record(
+                &Piece::named(vec!(
+                    Position::d2(1, 1),
+                    Position::d2(2, 1),
+                    Position::d2(3, 1),
+                ), "I"))
+            .record(
+                &Piece::new(vec!(
+                    Position::d2(1, 2),
+                    Position::d2(2, 2),
+                )).with_color(Rgb::new(0, 0, 0)));
+
+        let svg = solution.to_svg(10);
+
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"30\" height=\"20\" viewBox=\"0 0 30 20\">\n"));
+        assert_eq!(svg.matches("<rect ").count(), 5);
+        assert_eq!(svg.matches("<g ").count(), 2);
+        assert!(svg.contains("<g id=\"piece0\" data-name=\"I\" fill=\"#e6194b\">\n<rect x=\"0\" y=\"0\" width=\"10\" height=\"10\"/>\n"));
+        assert!(svg.contains("<g id=\"piece1\" fill=\"#000000\">\n<rect x=\"0\" y=\"10\" width=\"10\" height=\"10\"/>\n"));
+    }
+
+    #[test]
+    fn to_svg_should_give_copies_distinct_ids() {
+        let solution =
+            Solution::empty()
+            .record(&Piece::named(vec!(Position::d2(0, 0)), "I"))
+            .record(&Piece::named(vec!(Position::d2(1, 0)), "I"));
+
+        let svg = solution.to_svg(10);
+
+        assert!(svg.contains("<g id=\"piece0\" data-name=\"I\""));
+        assert!(svg.contains("<g id=\"piece1\" data-name=\"I\""));
+    }
+
+    #[test]
+    fn to_json_should_follow_schema() {
+        let solution =
+            Solution::empty()
+            .record(
+                &Piece::named(vec!(
+                    Position::new(0, 0, 0),
+                    Position::new(1, 0, 0),
+                    Position::new(0, 1, 0),
+                    Position::new(0, 0, 1),
+                ), "tripod \"a\"").with_color(Rgb::new(0xff, 0, 0)))
+            .record(
+                &Piece::new(vec!(
+                    Position::new(1, 1, 1),
+                    Position::new(0, 1, 1),
+                    Position::new(1, 0, 1),
+                    Position::new(1, 1, 0),
+                )));
+
+        assert_eq!(solution.to_json(), String::from(concat!(
+            "[{\"name\":\"tripod \\\"a\\\"\",\"color\":\"#ff0000\",\"positions\":[[0,0,0],[0,0,1],[0,1,0],[1,0,0]]},",
+            "{\"name\":null,\"color\":null,\"positions\":[[0,1,1],[1,0,1],[1,1,0],[1,1,1]]}]",
+        )));
+    }
+
+    #[test]
+    fn to_obj_should_emit_a_cube_per_cell() {
+        let solution =
+            Solution::empty()
+            .record(
+                &Piece::named(vec!(
+                    Position::new(0, 0, 0),
+                    Position::new(1, 0, 0),
+                ), "domino"))
+            .record(
+                &Piece::new(vec!(
+                    Position::new(0, 1, 0),
+                )).with_color(Rgb::new(0, 0, 0)));
+
+        let obj = solution.to_obj(2.0);
+
+        assert_eq!(obj.lines().filter(|line| line.starts_with("v ")).count(), 24);
+        assert_eq!(obj.lines().filter(|line| line.starts_with("f ")).count(), 18);
+        assert_eq!(obj.lines().filter(|line| line.starts_with("o ")).collect::<Vec<_>>(), vec!("o domino", "o piece1"));
+        assert_eq!(obj.lines().filter(|line| line.starts_with("usemtl ")).collect::<Vec<_>>(), vec!("usemtl color_e6194b", "usemtl color_000000"));
+        assert!(obj.contains("v 4 2 2\n"));
+        assert!(obj.ends_with("f 18 19 23 22\n"));
+        assert_eq!(solution.to_mtl(), "newmtl color_e6194b\nKd 0.9019608 0.09803922 0.29411766\nnewmtl color_000000\nKd 0 0 0\n");
+    }
+}
@@ -0,0 +1,148 @@
+//! Dense representation of the occupied cells in a box.
+use super::piece::Position;
+use super::solver::Target;
+
+/// The cells of a box, each of which is either occupied or not. Looking up and
+/// changing a cell by its `Position` takes constant time.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct Grid {
+    origin: (i8, i8, i8),
+    dimensions: (usize, usize, usize),
+    cells: Vec<bool>,
+}
+
+impl Grid {
+    /// Create a `Grid` without occupied cells for the box with its minimum
+    /// corner at `origin` and with the given `(width, height, depth)`.
+    pub fn new(origin: (i8, i8, i8), dimensions: (usize, usize, usize)) -> Grid {
+        let (width, height, depth) = dimensions;
+        Grid { origin, dimensions, cells: vec!(false; width * height * depth) }
+    }
+
+    /// The minimum corner of the box.
+    pub fn origin(&self) -> Position<(i8, i8, i8)> {
+        let (x, y, z) = self.origin;
+        Position::new(x, y, z)
+    }
+
+    /// The `(width, height, depth)` of the box.
+    pub fn dimensions(&self) -> (usize, usize, usize) {
+        self.dimensions
+    }
+
+    /// Determine if the cell at `position` is occupied. A `Position` outside the
+    /// box is not.
+    pub fn get(&self, position: &Position<(i8, i8, i8)>) -> bool {
+        self.index(position).is_some_and(|index| self.cells[index])
+    }
+
+    /// Mark the cell at `position` as occupied or not. Returns `false`, and
+    /// leaves the `Grid` unchanged, when `position` lies outside the box.
+    pub fn set(&mut self, position: &Position<(i8, i8, i8)>, occupied: bool) -> bool {
+        match self.index(position) {
+            Some(index) => {
+                self.cells[index] = occupied;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The number of occupied cells.
+    pub fn volume(&self) -> usize {
+        self.cells.iter().filter(|occupied| **occupied).count()
+    }
+
+    /// The index of `position` in the box, ordered like `Position`s are.
+    fn index(&self, position: &Position<(i8, i8, i8)>) -> Option<usize> {
+        let (x, y, z) = *position.coordinates();
+        let (width, height, depth) = self.dimensions;
+        let offset = |coordinate: i8, origin: i8, size: usize| {
+            let offset = coordinate as isize - origin as isize;
+            if 0 <= offset && (offset as usize) < size { Some(offset as usize) } else { None }
+        };
+        let dx = offset(x, self.origin.0, width)?;
+        let dy = offset(y, self.origin.1, height)?;
+        let dz = offset(z, self.origin.2, depth)?;
+        Some((dx * height + dy) * depth + dz)
+    }
+
+    /// The index of the first occupied cell at or after `start`.
+    pub(crate) fn first_occupied(&self, start: usize) -> Option<usize> {
+        (start..self.cells.len()).find(|index| self.cells[*index])
+    }
+
+    /// The `Position` of the cell at `index`.
+    pub(crate) fn position(&self, index: usize) -> Position<(i8, i8, i8)> {
+        let (_, height, depth) = self.dimensions;
+        let dz = index % depth;
+        let dy = (index / depth) % height;
+        let dx = index / (depth * height);
+        Position::new(
+            (self.origin.0 as isize + dx as isize) as i8,
+            (self.origin.1 as isize + dy as isize) as i8,
+            (self.origin.2 as isize + dz as isize) as i8,
+        )
+    }
+}
+
+/// Occupies the cells of the `Target` in its bounding box.
+impl From<&Target<(i8, i8, i8)>> for Grid {
+    fn from(target: &Target<(i8, i8, i8)>) -> Self {
+        let origin = match target.bounding_box() {
+            Some((minimum, _)) => *minimum.coordinates(),
+            None => (0, 0, 0),
+        };
+        let mut grid = Grid::new(origin, target.dimensions());
+        for position in target.positions() {
+            grid.set(position, true);
+        }
+        grid
+    }
+}
+
+/// The `Target` of the occupied cells, in sorted order.
+impl From<Grid> for Target<(i8, i8, i8)> {
+    fn from(grid: Grid) -> Self {
+        (0..grid.cells.len())
+            .filter(|index| grid.cells[*index])
+            .map(|index| grid.position(index))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grid_should_round_trip_through_target() {
+        let target = Target::new(vec!(
+            Position::new(1, -1, 0),
+            Position::new(1, 0, 0),
+            Position::new(2, 0, 0),
+            Position::new(1, 0, 2),
+        ));
+
+        let grid = Grid::from(&target);
+        let round_trip: Target<(i8, i8, i8)> = grid.clone().into();
+
+        assert_eq!(grid.origin(), Position::new(1, -1, 0));
+        assert_eq!(grid.dimensions(), (2, 2, 3));
+        assert_eq!(grid.volume(), 4);
+        assert_eq!(round_trip, target);
+    }
+
+    #[test]
+    fn grid_should_get_and_set_cells() {
+        let mut grid = Grid::new((0, 0, 0), (2, 2, 2));
+
+        assert!(grid.set(&Position::new(1, 0, 1), true));
+        assert!(!grid.set(&Position::new(2, 0, 0), true));
+
+        assert!(grid.get(&Position::new(1, 0, 1)));
+        assert!(!grid.get(&Position::new(0, 0, 0)));
+        assert!(!grid.get(&Position::new(-1, 0, 0)));
+        assert_eq!(grid.volume(), 1);
+    }
+}
@@ -0,0 +1,131 @@
+//! Backtracking that remembers the states from which no solution can be found.
+use std::collections::HashSet;
+use std::hash::Hash;
+
+use super::vector::{VectorAdd, VectorDifference};
+use super::piece::{CubeSymmetry, MinimumPosition, Normalizable, Piece, Positionable, Transformable};
+use super::pieces::{Bag, Bound};
+use super::solver::{SolveStats, Solution, Target};
+
+/// The orientations of a `Template`, with the `CubeSymmetry` that produced each.
+type Orientations<T> = Vec<(CubeSymmetry, Piece<T>)>;
+
+/// A remaining `Target` together with the number of copies that remain of
+/// every `Template` of the `Bag`.
+type State<T> = (Target<T>, Vec<usize>);
+
+/// The parts of the search that do not change from node to node.
+struct Memo<T> {
+    orientations: Vec<Orientations<T>>,
+    optional: Vec<bool>,
+    dead: HashSet<State<T>>,
+    stats: SolveStats,
+}
+
+/// Variant of the `solve` method that remembers every state of the search from
+/// which no solution could be found, and prunes the search when it reaches such
+/// a state again along a different path.
+///
+/// A state is the remaining `Target`, which compares and hashes as a set of
+/// `Position`s, together with the number of copies that remain of every
+/// `Template`. Different orders of placing the same `Piece`s often lead to the
+/// same state, so this pays off for puzzles with many transpositions, at the
+/// cost of memory for the dead states. It finds the same solutions as `solve`.
+/// Returns the `SolveStats` of the search.
+pub fn solve_memoized<F, T>(target: &Target<T>, bag: Bag<T>, when_solved: &mut F) -> SolveStats where F: (FnMut(Solution<T>)) + Sized, T: Hash + Clone + PartialOrd + Ord + Transformable + Normalizable<T> + VectorDifference<T> + VectorAdd<T> {
+    let (bounds, orientations): (Vec<Bound>, Vec<Orientations<T>>) = bag
+        .into_entries()
+        .map(|(bound, template)| (bound, template.into_iter().collect_with_symmetries()))
+        .unzip();
+    let mut memo = Memo {
+        orientations,
+        optional: bounds.iter().map(Bound::is_optional).collect(),
+        dead: HashSet::new(),
+        stats: SolveStats::default(),
+    };
+    let mut counts: Vec<usize> = bounds.iter().map(Bound::count).collect();
+    search(target, &mut counts, Solution::empty(), 0, &mut memo, when_solved);
+    memo.stats
+}
+
+/// Search the state for solutions, and return whether any was found.
+fn search<F, T>(target: &Target<T>, counts: &mut [usize], partial_solution: Solution<T>, depth: usize, memo: &mut Memo<T>, when_solved: &mut F) -> bool where F: (FnMut(Solution<T>)) + Sized, T: Hash + Clone + PartialOrd + Ord + VectorDifference<T> + VectorAdd<T> {
+    memo.stats.nodes_visited += 1;
+    memo.stats.max_depth = memo.stats.max_depth.max(depth);
+    let open_position = match target.minimum_position() {
+        Some(open_position) => open_position,
+        None => {
+            let satisfied = counts.iter().zip(&memo.optional).all(|(count, optional)| *count == 0 || *optional);
+            if satisfied {
+                memo.stats.solutions_found += 1;
+                when_solved(partial_solution);
+            }
+            return satisfied;
+        }
+    };
+    let state: State<T> = (target.clone(), counts.to_vec());
+    if memo.dead.contains(&state) {
+        return false;
+    }
+    let mut solved = false;
+    for index in 0..counts.len() {
+        if counts[index] == 0 {
+            continue;
+        }
+        counts[index] -= 1;
+        for orientation in 0..memo.orientations[index].len() {
+            let (symmetry, piece) = &memo.orientations[index][orientation];
+            let candidate = piece.translated(&piece.minimum_position().unwrap().to(&open_position));
+            memo.stats.placements_tried += 1;
+            if target.fits(&candidate) {
+                let remaining_target = target.place(&candidate);
+                let candidate_solution = partial_solution.record_with_symmetry(&candidate, Some(*symmetry));
+                solved |= search(&remaining_target, counts, candidate_solution, depth + 1, memo, when_solved);
+            }
+        }
+        counts[index] += 1;
+    }
+    if !solved {
+        memo.dead.insert(state);
+    }
+    solved
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::piece::{Position, Template};
+    use super::super::solver;
+    use super::*;
+
+    #[test]
+    fn solve_memoized_should_find_the_same_solutions_in_fewer_nodes() {
+        let target = Target::cuboid(3, 3, 2);
+        let bag = Bag::new(vec!(
+            (2,Template::new(vec!(
+                Position::new(0, 0, 0),
+                Position::new(1, 0, 0),
+                Position::new(0, 1, 0),
+                Position::new(1, 1, 0),
+            ))),
+            (3,Template::new(vec!(
+                Position::new(0, 0, 0),
+                Position::new(1, 0, 0),
+                Position::new(2, 0, 0),
+            ))),
+            (4,Template::new(vec!(
+                Position::new(0, 0, 0),
+            ))),
+        ));
+
+        let mut expected: Vec<String> = vec!();
+        let plain = solver::solve_with_stats(&target, bag.clone(), &mut |solution| expected.push(format!("{}", solution)));
+        let mut actual: Vec<String> = vec!();
+        let memoized = solve_memoized(&target, bag, &mut |solution| actual.push(format!("{}", solution)));
+
+        expected.sort();
+        actual.sort();
+        assert_eq!(expected.len(), 236);
+        assert_eq!(actual, expected);
+        assert!(memoized.nodes_visited < plain.nodes_visited);
+    }
+}
@@ -1,6 +1,13 @@
 //! Describing and solving packing problems.
 
 mod vector;
+pub mod bitmask;
+pub mod builder;
+pub mod dlx;
+mod export;
+pub mod grid;
+pub mod memoized;
+pub mod mutating;
 pub mod piece;
 pub mod pieces;
 pub mod solver;
@@ -0,0 +1,123 @@
+//! Backtracking that updates a single `Grid` of open cells in place.
+use super::grid::Grid;
+use super::piece::{CubeSymmetry, MinimumPosition, Piece, Positionable, Translatable};
+use super::pieces::{Bag, Bound};
+use super::solver::Solution;
+
+type Coordinates = (i8, i8, i8);
+
+/// The orientations of a `Template`, with the `CubeSymmetry` that produced each.
+type Orientations = Vec<(CubeSymmetry, Piece<Coordinates>)>;
+
+/// The state of the search, updated when a `Piece` is placed and restored when
+/// it is taken out again.
+struct Board {
+    open: Grid,
+    orientations: Vec<Orientations>,
+    counts: Vec<usize>,
+    optional: Vec<bool>,
+    placed: Vec<(CubeSymmetry, Piece<Coordinates>)>,
+}
+
+/// Variant of the `solve` method that keeps the open cells in a single `Grid`,
+/// e.g. `Grid::from(&target)`, whose occupied cells are the ones to pack.
+/// Placing a `Piece` clears its cells and taking it out again sets them, in
+/// constant time per cell, instead of creating a new `Target`, `Bag` and
+/// `Solution` at every node. The orientations of every `Template` are
+/// determined once, up front. It finds the same solutions as `solve`, but not
+/// in the same order.
+pub fn solve_mutating<F>(open: Grid, bag: Bag<Coordinates>, when_solved: &mut F) where F: (FnMut(Solution<Coordinates>)) + Sized {
+    let (bounds, orientations): (Vec<Bound>, Vec<Orientations>) = bag
+        .into_entries()
+        .map(|(bound, template)| (bound, template.into_iter().collect_with_symmetries()))
+        .unzip();
+    let mut board = Board {
+        open,
+        orientations,
+        counts: bounds.iter().map(Bound::count).collect(),
+        optional: bounds.iter().map(|bound| matches!(bound, Bound::UpTo(_))).collect(),
+        placed: vec!(),
+    };
+    backtrack(&mut board, 0, when_solved)
+}
+
+/// Every cell before `start` is packed, because the first open cell is covered
+/// at every node.
+fn backtrack<F>(board: &mut Board, start: usize, when_solved: &mut F) where F: (FnMut(Solution<Coordinates>)) + Sized {
+    let first = match board.open.first_occupied(start) {
+        Some(first) => first,
+        None => {
+            if board.counts.iter().zip(&board.optional).all(|(count, optional)| *count == 0 || *optional) {
+                when_solved(board.placed.iter().fold(Solution::empty(), |solution, (symmetry, piece)| solution.record_with_symmetry(piece, Some(*symmetry))));
+            }
+            return;
+        }
+    };
+    let open_position = board.open.position(first);
+    for index in 0..board.counts.len() {
+        if board.counts[index] == 0 {
+            continue;
+        }
+        board.counts[index] -= 1;
+        for orientation in 0..board.orientations[index].len() {
+            let (symmetry, piece) = &board.orientations[index][orientation];
+            let translation = piece.minimum_position().unwrap().to(&open_position);
+            let fits = piece.positions().iter().all(|position| {
+                let mut position = position.clone();
+                position.translate(&translation);
+                board.open.get(&position)
+            });
+            if fits {
+                let piece = piece.translated(&translation);
+                for position in piece.positions() {
+                    board.open.set(position, false);
+                }
+                board.placed.push((*symmetry, piece));
+                backtrack(board, first, when_solved);
+                let (_, piece) = board.placed.pop().unwrap();
+                for position in piece.positions() {
+                    board.open.set(position, true);
+                }
+            }
+        }
+        board.counts[index] += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::piece::{Position, Template};
+    use super::super::solver::{self, Target};
+    use super::*;
+
+    #[test]
+    fn solve_mutating_should_find_the_same_solutions() {
+        let target = Target::cuboid(3, 3, 2);
+        let bag = Bag::new(vec!(
+            (2,Template::new(vec!(
+                Position::new(0, 0, 0),
+                Position::new(1, 0, 0),
+                Position::new(0, 1, 0),
+                Position::new(1, 1, 0),
+            ))),
+            (3,Template::new(vec!(
+                Position::new(0, 0, 0),
+                Position::new(1, 0, 0),
+                Position::new(2, 0, 0),
+            ))),
+            (4,Template::new(vec!(
+                Position::new(0, 0, 0),
+            ))),
+        ));
+
+        let mut expected: Vec<(String, String)> = vec!();
+        solver::solve(&target, bag.clone(), &mut |solution| expected.push((format!("{}", solution), format!("{:?}", solution.symmetries()))));
+        let mut actual: Vec<(String, String)> = vec!();
+        solve_mutating(Grid::from(&target), bag, &mut |solution| actual.push((format!("{}", solution), format!("{:?}", solution.symmetries()))));
+
+        expected.sort();
+        actual.sort();
+        assert_eq!(expected.len(), 236);
+        assert_eq!(actual, expected);
+    }
+}
@@ -0,0 +1,70 @@
+//! Incremental construction of pieces.
+
+use super::{Piece, PieceError, Position};
+
+/// Describe a `Piece` cell by cell. The cells are checked when the `Piece` is
+/// built.
+#[derive(Debug, Clone, Default)]
+pub struct PieceBuilder {
+    positions: Vec<Position<(i8, i8, i8)>>,
+    name: Option<String>,
+}
+
+impl PieceBuilder {
+    /// Start describing a `Piece` without cells.
+    pub fn new() -> PieceBuilder {
+        PieceBuilder { positions: vec!(), name: None }
+    }
+
+    /// Add the cell at the given coordinates.
+    pub fn cell(mut self, x: i8, y: i8, z: i8) -> PieceBuilder {
+        self.positions.push(Position::new(x, y, z));
+        self
+    }
+
+    /// Name the `Piece`.
+    pub fn name<S>(self, name: S) -> PieceBuilder where S: Into<String> {
+        PieceBuilder { name: Some(name.into()), ..self }
+    }
+
+    /// Create the `Piece`. Fails when a cell was added more than once.
+    pub fn build(self) -> Result<Piece<(i8, i8, i8)>, PieceError> {
+        let piece = Piece::try_new(self.positions)?;
+        Ok(match self.name {
+            Some(name) => Piece::named(piece.iter().collect(), name),
+            None => piece,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builder_should_create_staircase() {
+        let piece = (0..3)
+            .fold(PieceBuilder::new().name("stairs"), |builder, step| builder.cell(step, step, 0).cell(step + 1, step, 0))
+            .build();
+
+        assert_eq!(piece, Ok(Piece::named(vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, 0, 0),
+            Position::new(1, 1, 0),
+            Position::new(2, 1, 0),
+            Position::new(2, 2, 0),
+            Position::new(3, 2, 0),
+        ), "stairs")));
+    }
+
+    #[test]
+    fn builder_should_report_duplicate_cell() {
+        let piece = PieceBuilder::new()
+            .cell(0, 0, 0)
+            .cell(1, 0, 0)
+            .cell(0, 0, 0)
+            .build();
+
+        assert_eq!(piece, Err(PieceError::DuplicateCell));
+    }
+}
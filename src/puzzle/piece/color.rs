@@ -0,0 +1,40 @@
+//! Colors that can be assigned to pieces.
+
+use std::fmt::{Display, Formatter, Error};
+
+/// A color described by its red, green and blue components.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rgb {
+    /// The red component.
+    pub red: u8,
+    /// The green component.
+    pub green: u8,
+    /// The blue component.
+    pub blue: u8,
+}
+
+impl Rgb {
+    /// Create a color from its components.
+    pub fn new(red: u8, green: u8, blue: u8) -> Rgb {
+        Rgb { red, green, blue }
+    }
+}
+
+impl Display for Rgb {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "#{:02x}{:02x}{:02x}", self.red, self.green, self.blue)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colors_should_display_as_hex() {
+        let color = Rgb::new(255, 128, 0);
+
+        assert_eq!(format!("{}", color), String::from("#ff8000"));
+    }
+}
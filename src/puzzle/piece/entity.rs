@@ -1,28 +1,112 @@
 //! Entities that get packed
 
 use std::fmt::{Formatter, Display, Error};
+use std::iter::FromIterator;
 
-use super::super::vector::VectorAdd;
-use super::{Position, MinimumPosition, Translatable, Translation, Transformable, CubeSymmetry};
+use super::super::vector::{VectorAdd, VectorDifference, VectorExtremes};
+use super::{bounding_box, Axis, Rgb, Direction, Normalizable, Position, Positionable, MinimumPosition, Translatable, Translation, Transformable, CubeSymmetry, CubeSymmetryIterator};
 
 /// A piece that get packed.
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "PieceFields<T>", bound(deserialize = "T: serde::Deserialize<'de> + Ord")))]
 pub struct Piece<T> {
     positions: Vec<Position<T>>,
-    name: Option<String>
+    name: Option<String>,
+    color: Option<Rgb>,
+}
+
+/// The fields of a `Piece` as they are read, before its `Position`s are put in
+/// order.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct PieceFields<T> {
+    positions: Vec<Position<T>>,
+    name: Option<String>,
+    color: Option<Rgb>,
+}
+
+/// Sorts the `Position`s, like `Piece::new` does.
+#[cfg(feature = "serde")]
+impl<T> From<PieceFields<T>> for Piece<T> where T: Ord {
+    fn from(fields: PieceFields<T>) -> Self {
+        let mut positions = fields.positions;
+        positions.sort();
+        Piece { positions, name: fields.name, color: fields.color }
+    }
+}
+
+/// Reasons why a collection of `Position`s does not describe a `Piece`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum PieceError {
+    /// A cell occurs more than once.
+    DuplicateCell,
+}
+
+impl Display for PieceError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        match *self {
+            PieceError::DuplicateCell => write!(f, "a cell occurs more than once"),
+        }
+    }
+}
+
+impl std::error::Error for PieceError {}
+
+impl<T> Piece<T> {
+    /// The name of this `Piece`, if any.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// The `Position`s of this `Piece`, in sorted order.
+    pub(crate) fn positions(&self) -> &[Position<T>] {
+        &self.positions
+    }
 }
 
 impl<T> Piece<T> where T: PartialOrd + Ord + Clone {
     /// Create a new `Piece` from a collection of `Position`s.
     pub fn new(mut positions: Vec<Position<T>>) -> Piece<T> {
         positions.sort();
-        Piece { positions, name: None }
+        Piece { positions, name: None, color: None }
+    }
+
+    /// Create a new `Piece` from a collection of `Position`s, that fails when a
+    /// `Position` occurs more than once. Unlike `new`, `len` of the `Piece` is
+    /// then guaranteed to be its number of cells.
+    pub fn try_new(positions: Vec<Position<T>>) -> Result<Piece<T>, PieceError> {
+        let piece = Piece::new(positions);
+        if piece.positions.windows(2).any(|pair| pair[0] == pair[1]) {
+            return Err(PieceError::DuplicateCell);
+        }
+        Ok(piece)
     }
 
     /// Create a named `Piece` from a collection of `Position`s.
     pub fn named<S>(mut positions: Vec<Position<T>>, name: S) -> Piece<T> where S: Into<String> {
         positions.sort();
-        Piece { positions, name: Some(name.into()) }
+        Piece { positions, name: Some(name.into()), color: None }
+    }
+
+    /// Create a colored `Piece` from this `Piece`.
+    pub fn with_color(self, color: Rgb) -> Piece<T> {
+        Piece { color: Some(color), ..self }
+    }
+
+    /// The color assigned to this `Piece`, if any.
+    pub fn color(&self) -> Option<Rgb> {
+        self.color
+    }
+
+    /// The number of cells this `Piece` occupies.
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// Determine if this `Piece` occupies no cells.
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
     }
 
     /// Determine if a `Position` is contained in this `Piece`.
@@ -30,14 +114,174 @@ impl<T> Piece<T> where T: PartialOrd + Ord + Clone {
         self.positions.contains(position)
     }
 
+    /// Determine if every `Position` of this `Piece` is also occupied by
+    /// `other`.
+    pub fn is_subset_of(&self, other: &Piece<T>) -> bool {
+        self.positions.iter().all(|position| other.contains(position))
+    }
+
+    /// Determine if both `Piece`s occupy the same `Position`s, regardless of
+    /// their names and colors.
+    pub fn same_shape(&self, other: &Piece<T>) -> bool {
+        self.positions == other.positions
+    }
+
     /// Create an `Iterator` that iterates over all `Position`s.
     pub fn iter(&self) -> PositionIterator<T> {
         let positions: Vec<Position<T>> = self.positions.to_vec();
         PositionIterator::new(positions)
     }
+
+    /// The minimum and maximum corner of the smallest box that contains this
+    /// `Piece`, or `None` for a `Piece` without cells.
+    pub fn bounding_box(&self) -> Option<(Position<T>, Position<T>)> where T: VectorExtremes<T> {
+        bounding_box(&self.positions)
+    }
+
+    /// A copy of this `Piece`, moved by the `Translation`.
+    pub fn translated(&self, translation: &Translation<T>) -> Piece<T> where T: VectorAdd<T> {
+        let mut piece = self.clone();
+        piece.translate(translation);
+        piece
+    }
+
+    /// A copy of this `Piece`, with the symmetry applied.
+    pub fn transformed(&self, symmetry: &CubeSymmetry) -> Piece<T> where T: Transformable {
+        let mut piece = self.clone();
+        piece.transform(symmetry);
+        piece
+    }
+}
+
+impl<T> Piece<T> where T: Clone + PartialOrd + Ord + Transformable + VectorDifference<T> + VectorAdd<T> {
+    /// The rotations that map this `Piece` onto itself, up to a translation.
+    /// This is the stabilizer of the `Piece`; a `Piece` without symmetry only
+    /// has the identity, and the number of distinct orientations of a `Piece`
+    /// is 24 divided by the number of its symmetries.
+    pub fn symmetries(&self) -> Vec<CubeSymmetry> {
+        let minimum = match self.minimum_position() {
+            Some(minimum) => minimum,
+            None => return CubeSymmetryIterator::rotations_only().collect(),
+        };
+        CubeSymmetryIterator::rotations_only()
+            .filter(|symmetry| {
+                let image = self.transformed(symmetry);
+                let translation = image.minimum_position().unwrap().to(&minimum);
+                image.translated(&translation).positions == self.positions
+            })
+            .collect()
+    }
+}
+
+impl<T> Piece<T> where T: Clone + PartialOrd + Ord + Transformable + Normalizable<T> + VectorAdd<T> {
+    /// Move this `Piece` so that its minimum `Position` is at the origin.
+    pub fn normalize(&mut self) {
+        if let Some(minimum) = self.minimum_position() {
+            self.translate(&minimum.to_reference());
+        }
+    }
+
+    /// A copy of this `Piece`, moved so that its minimum `Position` is at the
+    /// origin.
+    pub fn normalized(&self) -> Piece<T> {
+        let mut piece = self.clone();
+        piece.normalize();
+        piece
+    }
+
+    /// The orientation of this `Piece`, moved to the origin, with the
+    /// lexicographically smallest `Position`s. Two `Piece`s have the same shape,
+    /// up to rotation and translation, exactly when their canonical forms have
+    /// the same shape. A `Piece` in two dimensions is turned and flipped over
+    /// within the plane.
+    pub fn canonical(&self) -> Piece<T> {
+        T::orienting_symmetries(false)
+            .into_iter()
+            .map(|symmetry| self.transformed(&symmetry).normalized())
+            .min_by(|left, right| left.positions.cmp(&right.positions))
+            .unwrap()
+    }
+}
+
+impl Piece<(i8, i8, i8)> {
+    /// Mirror this `Piece` in the plane perpendicular to `axis`, i.e. negate
+    /// that coordinate of every `Position`, and move it back to the origin. The
+    /// coordinate is mirrored within the extent of the `Piece` along `axis`
+    /// instead of negated, so it stays in range at the ends of the coordinates.
+    pub fn reflect(&mut self, axis: Axis) {
+        let coordinate = |position: &Position<(i8, i8, i8)>| {
+            let (x, y, z) = *position.coordinates();
+            match axis {
+                Axis::X => x,
+                Axis::Y => y,
+                Axis::Z => z,
+            }
+        };
+        let low = self.positions.iter().map(coordinate).min().unwrap_or(0);
+        let high = self.positions.iter().map(coordinate).max().unwrap_or(0);
+        let mirror = |value: i8| (i16::from(low) + i16::from(high) - i16::from(value)) as i8;
+        for position in &mut self.positions {
+            let (x, y, z) = *position.coordinates();
+            *position = match axis {
+                Axis::X => Position::new(mirror(x), y, z),
+                Axis::Y => Position::new(x, mirror(y), z),
+                Axis::Z => Position::new(x, y, mirror(z)),
+            };
+        }
+        self.positions.sort();
+        self.normalize();
+    }
+
+    /// Create a `Piece` by walking from `start` in the given `Direction`s. Every
+    /// visited `Position`, including `start`, becomes part of the `Piece`.
+    /// Returns `None` when the walk leaves the range of the coordinates.
+    pub fn from_walk(start: Position<(i8, i8, i8)>, directions: &[Direction]) -> Option<Piece<(i8, i8, i8)>> {
+        let mut current = start.clone();
+        let mut positions = vec!(start);
+        for direction in directions {
+            current = current.step(*direction)?;
+            if !positions.contains(&current) {
+                positions.push(current.clone());
+            }
+        }
+        Some(Piece::new(positions))
+    }
+
+    /// The number of cells with an even coordinate sum minus the number of cells
+    /// with an odd coordinate sum, i.e. the balance of the `Piece` on a 3D
+    /// checkerboard. Rotations keep the balance, translations might negate it.
+    pub fn color_balance(&self) -> i32 {
+        color_balance(&self.positions)
+    }
+
+    /// The average of the coordinates of the cells of this `Piece`. The
+    /// coordinates are not a number for an empty `Piece`.
+    pub fn center_of_mass(&self) -> (f32, f32, f32) {
+        let count = self.positions.len() as f32;
+        let (x, y, z) = self.positions.iter().fold((0.0, 0.0, 0.0), |(x, y, z), position| {
+            let (dx, dy, dz) = *position.coordinates();
+            (x + f32::from(dx), y + f32::from(dy), z + f32::from(dz))
+        });
+        (x / count, y / count, z / count)
+    }
+
+    /// The side length of the smallest cube that encloses this `Piece`, i.e.
+    /// the largest dimension of its bounding box. Pieces with a large enclosing
+    /// cube but few cells tend to be awkward to place.
+    pub fn min_enclosing_cube_size(&self) -> usize {
+        match self.bounding_box() {
+            Some((minimum, maximum)) => {
+                let (min_x, min_y, min_z) = *minimum.coordinates();
+                let (max_x, max_y, max_z) = *maximum.coordinates();
+                let size = |min: i8, max: i8| (max as isize - min as isize + 1) as usize;
+                size(min_x, max_x).max(size(min_y, max_y)).max(size(min_z, max_z))
+            }
+            None => 0,
+        }
+    }
 }
 
-impl Display for Piece<(i8,i8,i8)> {
+impl<N> Display for Piece<(N, N, N)> where N: Display {
     fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
         write!(f, "[")?;
         let name = self.name.clone().unwrap_or_else(|| String::from(""));
@@ -62,6 +306,12 @@ impl Display for Piece<(i8, i8)> {
     }
 }
 
+impl<T> FromIterator<Position<T>> for Piece<T> where T: PartialOrd + Ord + Clone {
+    fn from_iter<I>(positions: I) -> Self where I: IntoIterator<Item = Position<T>> {
+        Piece::new(positions.into_iter().collect())
+    }
+}
+
 impl<T> Transformable for Piece<T> where T: Transformable + PartialOrd + Ord {
     fn transform(&mut self, symmetry: &CubeSymmetry) {
         for position in &mut self.positions {
@@ -112,6 +362,17 @@ impl<T> Iterator for PositionIterator<T> where T: Clone {
     }
 }
 
+/// Checkerboard balance of a collection of `Position`s.
+pub(crate) fn color_balance<'a, I>(positions: I) -> i32 where I: IntoIterator<Item = &'a Position<(i8, i8, i8)>> {
+    positions
+        .into_iter()
+        .map(|position| {
+            let (x, y, z) = *position.coordinates();
+            if (x as i32 + y as i32 + z as i32) % 2 == 0 { 1 } else { -1 }
+        })
+        .sum()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -154,4 +415,295 @@ mod tests {
             Position::new(1, 1, -1),
         )));
     }
+
+    #[test]
+    fn translated_and_transformed_should_leave_source_unchanged() {
+        let piece = Piece::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, 0, 0),
+            Position::new(1, 1, 0),
+            Position::new(1, 1, 1),
+        ));
+        let original = piece.clone();
+
+        let translated = piece.translated(&Translation::new(5, -3, 0));
+        let transformed = piece.transformed(&CubeSymmetry::E2103);
+
+        assert_eq!(piece, original);
+        assert_eq!(translated, Piece::new(vec!(
+            Position::new(5, -3, 0),
+            Position::new(6, -3, 0),
+            Position::new(6, -2, 0),
+            Position::new(6, -2, 1),
+        )));
+        assert_eq!(transformed, Piece::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(0, 1, 0),
+            Position::new(1, 1, 0),
+            Position::new(1, 1, -1),
+        )));
+    }
+
+    #[test]
+    fn translated_copies_should_normalize_to_equal_pieces() {
+        let piece = Piece::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, 0, 0),
+            Position::new(1, 1, 0),
+        ));
+        let mut first = piece.translated(&Translation::new(3, 4, -5));
+        let second = piece.translated(&Translation::new(-2, 0, 7));
+
+        first.normalize();
+
+        assert_eq!(first, second.normalized());
+        assert_eq!(first.minimum_position(), Some(Position::new(0, 0, 0)));
+    }
+
+    #[test]
+    fn all_orientations_should_have_the_same_canonical_form() {
+        let piece = Piece::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, 0, 0),
+            Position::new(2, 0, 0),
+            Position::new(2, 1, 0),
+            Position::new(2, 1, 1),
+        ));
+        let canonical = piece.canonical();
+
+        for symmetry in CubeSymmetryIterator::rotations_only() {
+            let mut orientation = piece.clone();
+            orientation.transform(&symmetry);
+            orientation.translate(&Translation::new(3, -2, 7));
+
+            assert_eq!(orientation.canonical(), canonical);
+        }
+        assert_eq!(canonical.minimum_position(), Some(Position::new(0, 0, 0)));
+    }
+
+    #[test]
+    fn reflecting_chiral_piece_should_change_canonical_form() {
+        let chiral = Piece::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, 0, 0),
+            Position::new(2, 0, 0),
+            Position::new(2, 1, 0),
+            Position::new(2, 1, 1),
+        ));
+        let planar = Piece::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, 0, 0),
+            Position::new(2, 0, 0),
+            Position::new(2, 1, 0),
+        ));
+
+        for axis in [Axis::X, Axis::Y, Axis::Z] {
+            let mut mirror = chiral.clone();
+            mirror.reflect(axis);
+            assert_ne!(mirror.canonical(), chiral.canonical());
+            assert_eq!(mirror.minimum_position(), Some(Position::new(0, 0, 0)));
+
+            let mut mirror = planar.clone();
+            mirror.reflect(axis);
+            assert_eq!(mirror.canonical(), planar.canonical());
+        }
+    }
+
+    #[test]
+    fn reflecting_should_stay_in_the_range_of_the_coordinates() {
+        let mut piece = Piece::new(vec!(
+            Position::new(0, i8::MIN, 0),
+            Position::new(1, 0, 0),
+        ));
+
+        piece.reflect(Axis::Y);
+
+        assert_eq!(piece, Piece::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, i8::MIN, 0),
+        )));
+    }
+
+    #[test]
+    fn collecting_positions_should_create_piece() {
+        let positions = vec!(
+            Position::new(1, 0, 0),
+            Position::new(0, 0, 0),
+            Position::new(0, 1, 0),
+        );
+
+        assert_eq!(positions.clone().into_iter().collect::<Piece<_>>(), Piece::new(positions));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn pieces_should_sort_positions_when_deserialized() {
+        let json = "{\"positions\":[[1,0,0],[0,0,0]],\"name\":null,\"color\":null}";
+
+        let piece: Piece<(i8, i8, i8)> = serde_json::from_str(json).expect("to deserialize");
+        let copy: Piece<(i8, i8, i8)> = serde_json::from_str(&serde_json::to_string(&piece).expect("to serialize")).expect("to deserialize");
+
+        assert_eq!(piece, Piece::new(vec!(Position::new(1, 0, 0), Position::new(0, 0, 0))));
+        assert_eq!(copy, piece);
+    }
+
+    #[test]
+    fn piece_should_be_created_from_walk() {
+        let piece = Piece::from_walk(Position::new(0, 0, 0), &[Direction::PosX, Direction::PosY]);
+
+        assert_eq!(piece, Some(Piece::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, 0, 0),
+            Position::new(1, 1, 0),
+        ))));
+        assert_eq!(Piece::from_walk(Position::new(0, i8::MAX, 0), &[Direction::PosY]), None);
+    }
+
+    #[test]
+    fn try_new_should_reject_duplicate_positions() {
+        let positions = vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, 0, 0),
+            Position::new(0, 0, 0),
+        );
+
+        assert_eq!(Piece::try_new(positions.clone()), Err(PieceError::DuplicateCell));
+        assert_eq!(Piece::new(positions).len(), 3);
+        assert_eq!(Piece::try_new(vec!(Position::new(1, 0, 0), Position::new(0, 0, 0))), Ok(Piece::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, 0, 0),
+        ))));
+    }
+
+    #[test]
+    fn is_subset_of_should_compare_positions() {
+        let domino = Piece::new(vec!(Position::new(0, 0, 0), Position::new(1, 0, 0)));
+        let tromino = Piece::named(vec!(Position::new(0, 0, 0), Position::new(1, 0, 0), Position::new(1, 1, 0)), "L");
+
+        assert!(domino.is_subset_of(&tromino));
+        assert!(domino.is_subset_of(&domino));
+        assert!(!tromino.is_subset_of(&domino));
+        assert!(!domino.is_subset_of(&Piece::new(vec!(Position::new(0, 0, 0), Position::new(0, 1, 0)))));
+    }
+
+    #[test]
+    fn same_shape_should_ignore_names() {
+        let positions = vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, 0, 0),
+            Position::new(1, 1, 0),
+        );
+        let anonymous = Piece::new(positions.clone());
+        let named = Piece::named(positions, "V");
+
+        assert!(anonymous.same_shape(&named));
+        assert_ne!(anonymous, named);
+        assert!(!anonymous.same_shape(&Piece::new(vec!(Position::new(0, 0, 0)))));
+    }
+
+    #[test]
+    fn piece_should_count_cells() {
+        let piece = Piece::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, 0, 0),
+            Position::new(1, 1, 0),
+        ));
+
+        assert_eq!(piece.len(), 3);
+        assert!(!piece.is_empty());
+        assert!(Piece::<(i8, i8, i8)>::new(vec!()).is_empty());
+    }
+
+    #[test]
+    fn piece_should_determine_bounding_box() {
+        let piece = Piece::new(vec!(
+            Position::new(1, 2, 3),
+            Position::new(2, 2, 3),
+            Position::new(1, 3, 3),
+        ));
+
+        assert_eq!(piece.bounding_box(), Some((Position::new(1, 2, 3), Position::new(2, 3, 3))));
+        assert_eq!(Piece::<(i8, i8, i8)>::new(vec!()).bounding_box(), None);
+    }
+
+    #[test]
+    fn center_of_mass_should_be_midpoint_of_block() {
+        let block = Piece::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, 0, 0),
+            Position::new(0, 1, 0),
+            Position::new(1, 1, 0),
+        ));
+
+        assert_eq!(block.center_of_mass(), (0.5, 0.5, 0.0));
+        assert_eq!(block.translated(&Translation::new(2, 0, -4)).center_of_mass(), (2.5, 0.5, -4.0));
+    }
+
+    #[test]
+    fn piece_should_determine_min_enclosing_cube_size() {
+        let straight = Piece::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(0, 0, 1),
+            Position::new(0, 0, 2),
+            Position::new(0, 0, 3),
+        ));
+        let slab = Piece::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, 0, 0),
+            Position::new(0, 1, 0),
+            Position::new(1, 1, 0),
+        ));
+
+        assert_eq!(straight.min_enclosing_cube_size(), 4);
+        assert_eq!(slab.min_enclosing_cube_size(), 2);
+        assert_eq!(Piece::new(vec!(Position::new(i8::MIN, 0, 0), Position::new(i8::MAX, 0, 0))).min_enclosing_cube_size(), 256);
+    }
+
+    #[test]
+    fn piece_should_determine_its_symmetries() {
+        let slab = Piece::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, 0, 0),
+            Position::new(0, 1, 0),
+            Position::new(1, 1, 0),
+        ));
+        let l = Piece::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, 0, 0),
+            Position::new(2, 0, 0),
+            Position::new(0, 1, 0),
+        ));
+
+        assert_eq!(slab.symmetries().len(), 8);
+        assert_eq!(l.symmetries(), vec!(CubeSymmetry::E0123));
+    }
+
+    #[test]
+    fn piece_should_determine_color_balance() {
+        let t = Piece::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, 0, 0),
+            Position::new(2, 0, 0),
+            Position::new(1, 1, 0),
+        ));
+        let mut shifted = t.clone();
+        shifted.translate(&Translation::new(1, 0, 0));
+
+        assert_eq!(t.color_balance(), 2);
+        assert_eq!(shifted.color_balance(), -2);
+    }
+
+    #[test]
+    fn piece_should_keep_color_when_moved() {
+        let color = Rgb::new(255, 0, 0);
+        let mut piece = Piece::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, 0, 0),
+        )).with_color(color);
+
+        piece.transform(&CubeSymmetry::E2103);
+        piece.translate(&Translation::new(1, 2, 3));
+
+        assert_eq!(piece.color(), Some(color));
+    }
 }
@@ -2,14 +2,20 @@
 //!
 //! At the moment only objects that are aligned with an ordinary rectangular grid can be defined.
 
+mod color;
 mod symmetry;
 mod translation;
 mod position;
 mod entity;
+mod builder;
 mod template;
 
-pub use self::symmetry::{Transformable, CubeSymmetry, CubeSymmetryIterator};
+pub use self::color::Rgb;
+pub use self::symmetry::{Transformable, CubeSymmetry, CubeSymmetryIterator, SquareSymmetry, SquareSymmetryIterator};
 pub use self::translation::{Translatable, Translation};
-pub use self::position::{Position, Positionable, Normalizable, MinimumPosition};
-pub use self::entity::{Piece};
-pub use self::template::Template;
+pub use self::position::{Axis, Direction, Position, Positionable, Normalizable, MinimumPosition};
+pub(crate) use self::position::bounding_box;
+pub use self::entity::{Piece, PieceError};
+pub use self::builder::PieceBuilder;
+pub(crate) use self::entity::color_balance;
+pub use self::template::{Template, Orientations};
@@ -2,20 +2,84 @@
 use std::cmp::Ordering;
 use std::fmt::{Display, Formatter, Error};
 
-use super::super::vector::{VectorDifference, VectorAdd};
+use super::super::vector::{VectorDifference, VectorAdd, VectorExtremes};
 use super::{Transformable, CubeSymmetry, Translatable, Translation};
 
 /// Position of a cubelet.
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
 pub struct Position<T> {
     base: T,
 }
 
+impl<T> Position<T> {
+    /// The coordinates of this position.
+    pub fn coordinates(&self) -> &T {
+        &self.base
+    }
+}
+
 impl Position<(i8, i8, i8)> {
     /// Create  position at the given coordinates.
     pub fn new(x: i8, y: i8, z: i8) -> Position<(i8, i8, i8)> {
         Position { base: (x, y, z) }
     }
+
+    /// The neighbouring `Position` in the given `Direction`, or `None` when it
+    /// lies outside the range of the coordinates.
+    pub fn step(&self, direction: Direction) -> Option<Position<(i8, i8, i8)>> {
+        let (x, y, z) = self.base;
+        Some(match direction {
+            Direction::PosX => Position::new(x.checked_add(1)?, y, z),
+            Direction::NegX => Position::new(x.checked_sub(1)?, y, z),
+            Direction::PosY => Position::new(x, y.checked_add(1)?, z),
+            Direction::NegY => Position::new(x, y.checked_sub(1)?, z),
+            Direction::PosZ => Position::new(x, y, z.checked_add(1)?),
+            Direction::NegZ => Position::new(x, y, z.checked_sub(1)?),
+        })
+    }
+
+    /// The `Position`s that share a face with this `Position`, in the order of
+    /// `Direction::all`. Neighbours outside the range of the coordinates are
+    /// left out.
+    pub fn neighbors(&self) -> impl Iterator<Item = Position<(i8, i8, i8)>> + '_ {
+        IntoIterator::into_iter(Direction::all()).filter_map(move |direction| self.step(direction))
+    }
+}
+
+/// The directions along the axes in which one can step from a `Position`.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Direction {
+    /// Along the positive x-axis.
+    PosX,
+    /// Along the negative x-axis.
+    NegX,
+    /// Along the positive y-axis.
+    PosY,
+    /// Along the negative y-axis.
+    NegY,
+    /// Along the positive z-axis.
+    PosZ,
+    /// Along the negative z-axis.
+    NegZ,
+}
+
+/// The coordinate axes.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Axis {
+    /// The x-axis.
+    X,
+    /// The y-axis.
+    Y,
+    /// The z-axis.
+    Z,
+}
+
+impl Direction {
+    /// All six `Direction`s.
+    pub fn all() -> [Direction; 6] {
+        [Direction::PosX, Direction::NegX, Direction::PosY, Direction::NegY, Direction::PosZ, Direction::NegZ]
+    }
 }
 
 impl Position<(i8, i8)> {
@@ -23,8 +87,19 @@ impl Position<(i8, i8)> {
     pub fn d2(x: i8, y: i8) -> Position<(i8, i8)> {
         Position { base: (x, y) }
     }
-}
 
+    /// The `Position`s that share an edge with this `Position`. Neighbours
+    /// outside the range of the coordinates are left out.
+    pub fn neighbors(&self) -> impl Iterator<Item = Position<(i8, i8)>> {
+        let (x, y) = self.base;
+        IntoIterator::into_iter([
+            x.checked_add(1).map(|x| Position::d2(x, y)),
+            x.checked_sub(1).map(|x| Position::d2(x, y)),
+            y.checked_add(1).map(|y| Position::d2(x, y)),
+            y.checked_sub(1).map(|y| Position::d2(x, y)),
+        ]).flatten()
+    }
+}
 
 /// Move an entity to certain `Position`.
 pub trait Positionable<T> {
@@ -32,6 +107,12 @@ pub trait Positionable<T> {
     fn to(&self, other: &Self) -> Translation<T>;
 }
 
+impl<T> From<T> for Position<T> {
+    fn from(base: T) -> Self {
+        Position { base }
+    }
+}
+
 impl<T> Positionable<T> for Position<T> where T: VectorDifference<T> {
     fn to(&self, other: &Self) -> Translation<T> {
         let translation: T = self.base.difference(&other.base);
@@ -52,14 +133,20 @@ impl<T> Normalizable<T> for Position<T> where T: Normalizable<T> {
     }
 }
 
-impl Normalizable<(i8, i8, i8)> for (i8, i8, i8) {
-    fn to_reference(&self) -> Translation<(i8, i8, i8)> {
-        let translation = (-self.0, -self.1, -self.2);
+macro_rules! normalizable_triple {
+    ($($coordinate:ty),*) => {$(
+        impl Normalizable<($coordinate, $coordinate, $coordinate)> for ($coordinate, $coordinate, $coordinate) {
+            fn to_reference(&self) -> Translation<($coordinate, $coordinate, $coordinate)> {
+                let translation = (-self.0, -self.1, -self.2);
 
-        Translation::from(translation)
-    }
+                Translation::from(translation)
+            }
+        }
+    )*}
 }
 
+normalizable_triple!(i8, i16, i32);
+
 impl Normalizable<(i8, i8)> for (i8, i8) {
     fn to_reference(&self) -> Translation<(i8, i8)> {
         let translation = (-self.0, -self.1);
@@ -68,7 +155,7 @@ impl Normalizable<(i8, i8)> for (i8, i8) {
     }
 }
 
-impl Display for Position<(i8, i8, i8)> {
+impl<N> Display for Position<(N, N, N)> where N: Display {
     fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
         write!(f, "({}, {}, {})", self.base.0, self.base.1, self.base.2)
     }
@@ -80,6 +167,11 @@ impl Display for Position<(i8, i8)> {
     }
 }
 
+/// `Position`s are ordered like their coordinates, so `Position<(i8, i8, i8)>`
+/// is ordered lexicographically on `(x, y, z)`: first by x, then by y, then by
+/// z. This order is part of the contract of the solver, which fills the
+/// `Target` from its minimum open `Position` onwards. It scans along the z-axis
+/// first, then along the y-axis, and the x-axis last.
 impl<T> Ord for Position<T> where T: PartialOrd + Ord {
     fn cmp(&self, other: &Self) -> Ordering {
         self.base.cmp(&other.base)
@@ -94,7 +186,7 @@ impl<T> PartialOrd for Position<T> where T: PartialOrd {
 
 impl<T> Transformable for Position<T> where T: Transformable {
     fn transform(&mut self, symmetry: &CubeSymmetry) {
-        self.base.transform(&symmetry);
+        self.base.transform(symmetry);
     }
 }
 
@@ -104,6 +196,19 @@ impl<T> Translatable<T> for Position<T> where T: VectorAdd<T> {
     }
 }
 
+/// Determine the corners of the smallest box containing all `positions`, i.e.
+/// the component-wise minimum and maximum. Returns `None` when there are no
+/// positions.
+pub(crate) fn bounding_box<'a, T, I>(positions: I) -> Option<(Position<T>, Position<T>)> where T: VectorExtremes<T> + Clone + 'a, I: IntoIterator<Item = &'a Position<T>> {
+    positions.into_iter().fold(None, |corners, position| match corners {
+        None => Some((position.clone(), position.clone())),
+        Some((minimum, maximum)) => Some((
+            Position { base: minimum.base.minimum(&position.base) },
+            Position { base: maximum.base.maximum(&position.base) },
+        )),
+    })
+}
+
 /// Contract to find the minimal `Position`
 pub trait MinimumPosition<T> where T: PartialOrd + Ord {
     /// Return the minimal `Position` for the entity.
@@ -121,4 +226,71 @@ mod tests {
 
         assert_eq!(a, b);
     }
+
+    #[test]
+    fn positions_should_be_ordered_lexicographically_on_x_y_z() {
+        let mut positions = vec!(
+            Position::new(1, 0, 0),
+            Position::new(0, 1, 0),
+            Position::new(0, 0, 1),
+            Position::new(0, 1, -1),
+            Position::new(-1, 2, 2),
+            Position::new(0, 0, 0),
+        );
+        positions.sort();
+
+        assert_eq!(positions, vec!(
+            Position::new(-1, 2, 2),
+            Position::new(0, 0, 0),
+            Position::new(0, 0, 1),
+            Position::new(0, 1, -1),
+            Position::new(0, 1, 0),
+            Position::new(1, 0, 0),
+        ));
+        assert!(Position::d2(0, 5) < Position::d2(1, 0));
+    }
+
+    #[test]
+    fn positions_should_step_in_direction() {
+        let origin = Position::new(0, 0, 0);
+
+        assert_eq!(origin.step(Direction::PosX), Some(Position::new(1, 0, 0)));
+        assert_eq!(origin.step(Direction::NegY), Some(Position::new(0, -1, 0)));
+        assert_eq!(origin.step(Direction::PosZ), Some(Position::new(0, 0, 1)));
+    }
+
+    #[test]
+    fn steps_should_stop_at_the_range_of_the_coordinates() {
+        let corner = Position::new(i8::MAX, 0, i8::MIN);
+
+        assert_eq!(corner.step(Direction::PosX), None);
+        assert_eq!(corner.step(Direction::NegX), Some(Position::new(i8::MAX - 1, 0, i8::MIN)));
+        assert_eq!(corner.step(Direction::NegZ), None);
+        assert_eq!(corner.neighbors().count(), 4);
+        assert_eq!(Position::d2(i8::MIN, i8::MAX).neighbors().collect::<Vec<_>>(), vec!(
+            Position::d2(i8::MIN + 1, i8::MAX),
+            Position::d2(i8::MIN, i8::MAX - 1),
+        ));
+    }
+
+    #[test]
+    fn neighbors_should_be_unit_steps_along_axes() {
+        let mut neighbors: Vec<Position<(i8, i8, i8)>> = Position::new(0, 0, 0).neighbors().collect();
+        neighbors.sort();
+
+        assert_eq!(neighbors, vec!(
+            Position::new(-1, 0, 0),
+            Position::new(0, -1, 0),
+            Position::new(0, 0, -1),
+            Position::new(0, 0, 1),
+            Position::new(0, 1, 0),
+            Position::new(1, 0, 0),
+        ));
+        assert_eq!(Position::d2(2, 3).neighbors().collect::<Vec<_>>(), vec!(
+            Position::d2(3, 3),
+            Position::d2(1, 3),
+            Position::d2(2, 4),
+            Position::d2(2, 2),
+        ));
+    }
 }
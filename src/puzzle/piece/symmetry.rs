@@ -1,16 +1,41 @@
 //! Symmetries of pieces.
 
+use std::fmt::{Display, Error, Formatter};
+
 /// Contract how various entities transform under the symmetries of the cube.
 pub trait Transformable {
     /// Apply a symmetry and transform the entity.
     fn transform(&mut self, symmetry: &CubeSymmetry);
+
+    /// The symmetries that orient an entity made of these coordinates, with or
+    /// without the `reflections`. By default these are the symmetries of the
+    /// cube.
+    fn orienting_symmetries(reflections: bool) -> Vec<CubeSymmetry> where Self: Sized {
+        if reflections {
+            CubeSymmetryIterator::with_reflections().collect()
+        } else {
+            CubeSymmetryIterator::rotations_only().collect()
+        }
+    }
+
+    /// The symmetry that reverses the order of the coordinates, so that
+    /// comparing images compares the last coordinate first. By default this
+    /// swaps x and z.
+    fn reversing_symmetry() -> CubeSymmetry where Self: Sized {
+        CubeSymmetry::R0132
+    }
 }
 
 /// Symmetries of the Cube.
 ///
-/// The group of symmetries of the cube is isomorphic to S<sub>4</sub>. Here we
-/// use that fact as a naming convention for our elements.
-#[derive(Clone)]
+/// The group of rotations of the cube is isomorphic to S<sub>4</sub>. Here we
+/// use that fact as a naming convention for our elements. The rotations are
+/// the variants starting with `E`.
+///
+/// Each rotation `Exxxx` has a reflected counterpart `Rxxxx`: the rotation
+/// followed by the point reflection through the origin. Together they form the
+/// full group of 48 symmetries of the cube.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub enum CubeSymmetry {
     #[allow(missing_docs)]
     E0123,
@@ -63,17 +88,240 @@ pub enum CubeSymmetry {
     E3201,
     #[allow(missing_docs)]
     E3210,
+
+    #[allow(missing_docs)]
+    R0123,
+    #[allow(missing_docs)]
+    R0132,
+    #[allow(missing_docs)]
+    R0213,
+    #[allow(missing_docs)]
+    R0231,
+    #[allow(missing_docs)]
+    R0312,
+    #[allow(missing_docs)]
+    R0321,
+
+    #[allow(missing_docs)]
+    R1023,
+    #[allow(missing_docs)]
+    R1032,
+    #[allow(missing_docs)]
+    R1203,
+    #[allow(missing_docs)]
+    R1230,
+    #[allow(missing_docs)]
+    R1302,
+    #[allow(missing_docs)]
+    R1320,
+
+    #[allow(missing_docs)]
+    R2013,
+    #[allow(missing_docs)]
+    R2031,
+    #[allow(missing_docs)]
+    R2103,
+    #[allow(missing_docs)]
+    R2130,
+    #[allow(missing_docs)]
+    R2301,
+    #[allow(missing_docs)]
+    R2310,
+
+    #[allow(missing_docs)]
+    R3012,
+    #[allow(missing_docs)]
+    R3021,
+    #[allow(missing_docs)]
+    R3102,
+    #[allow(missing_docs)]
+    R3120,
+    #[allow(missing_docs)]
+    R3201,
+    #[allow(missing_docs)]
+    R3210,
+}
+
+const SYMMETRIES: [CubeSymmetry; 48] = [
+    CubeSymmetry::E0123, CubeSymmetry::E0132, CubeSymmetry::E0213, CubeSymmetry::E0231, CubeSymmetry::E0312, CubeSymmetry::E0321,
+    CubeSymmetry::E1023, CubeSymmetry::E1032, CubeSymmetry::E1203, CubeSymmetry::E1230, CubeSymmetry::E1302, CubeSymmetry::E1320,
+    CubeSymmetry::E2013, CubeSymmetry::E2031, CubeSymmetry::E2103, CubeSymmetry::E2130, CubeSymmetry::E2301, CubeSymmetry::E2310,
+    CubeSymmetry::E3012, CubeSymmetry::E3021, CubeSymmetry::E3102, CubeSymmetry::E3120, CubeSymmetry::E3201, CubeSymmetry::E3210,
+    CubeSymmetry::R0123, CubeSymmetry::R0132, CubeSymmetry::R0213, CubeSymmetry::R0231, CubeSymmetry::R0312, CubeSymmetry::R0321,
+    CubeSymmetry::R1023, CubeSymmetry::R1032, CubeSymmetry::R1203, CubeSymmetry::R1230, CubeSymmetry::R1302, CubeSymmetry::R1320,
+    CubeSymmetry::R2013, CubeSymmetry::R2031, CubeSymmetry::R2103, CubeSymmetry::R2130, CubeSymmetry::R2301, CubeSymmetry::R2310,
+    CubeSymmetry::R3012, CubeSymmetry::R3021, CubeSymmetry::R3102, CubeSymmetry::R3120, CubeSymmetry::R3201, CubeSymmetry::R3210,
+];
+
+/// Where each of the `SYMMETRIES` takes `(1, 2, 3)`.
+const IMAGES: [(i8, i8, i8); 48] = [
+    (1, 2, 3), (-3, -2, -1), (-1, -3, -2), (2, 3, 1), (3, 1, 2), (-2, -1, -3),
+    (3, -2, 1), (-1, 2, -3), (-3, -1, 2), (-2, 1, 3), (1, 3, -2), (2, -3, -1),
+    (-2, 3, -1), (1, -3, 2), (2, 1, -3), (3, -1, -2), (-1, -2, 3), (-3, 2, 1),
+    (2, -1, 3), (-3, 1, -2), (-2, -3, 1), (-1, 3, 2), (3, 2, -1), (1, -2, -3),
+    (-1, -2, -3), (3, 2, 1), (1, 3, 2), (-2, -3, -1), (-3, -1, -2), (2, 1, 3),
+    (-3, 2, -1), (1, -2, 3), (3, 1, -2), (2, -1, -3), (-1, -3, 2), (-2, 3, 1),
+    (2, -3, 1), (-1, 3, -2), (-2, -1, 3), (-3, 1, 2), (1, 2, -3), (3, -2, -1),
+    (-2, 1, -3), (3, -1, 2), (2, 3, -1), (1, -3, -2), (-3, -2, 1), (-1, 2, 3),
+];
+
+impl CubeSymmetry {
+    /// All 48 symmetries of the cube, the 24 rotations followed by the 24
+    /// reflections.
+    pub fn all() -> &'static [CubeSymmetry] {
+        &SYMMETRIES
+    }
+
+    /// Determine if this symmetry is a rotation, i.e. it does not reflect.
+    pub fn is_proper(&self) -> bool {
+        self.index() < 24
+    }
+
+    /// The rotation part of this symmetry. For a rotation this is the symmetry
+    /// itself, for a reflection `Rxxxx` it is the corresponding `Exxxx`.
+    pub fn rotation(&self) -> CubeSymmetry {
+        SYMMETRIES[self.index() % 24]
+    }
+
+    /// The symmetry equivalent to first applying `other` and then `self`.
+    pub fn compose(&self, other: &CubeSymmetry) -> CubeSymmetry {
+        let mut image = other.image();
+        image.transform(self);
+        CubeSymmetry::with_image(image).expect("symmetries to compose to a symmetry")
+    }
+
+    /// The symmetry that undoes this symmetry. Where this symmetry takes the
+    /// coordinate `i` to `j`, the inverse takes `j` back to `i`.
+    pub fn inverse(&self) -> CubeSymmetry {
+        let image = self.image();
+        let mut inverse: [i8; 3] = [0; 3];
+        for (index, coordinate) in [image.0, image.1, image.2].iter().enumerate() {
+            inverse[coordinate.unsigned_abs() as usize - 1] = coordinate.signum() * (index as i8 + 1);
+        }
+        CubeSymmetry::with_image((inverse[0], inverse[1], inverse[2])).expect("inverse to be a symmetry")
+    }
+
+    /// The position of this symmetry in `SYMMETRIES`, which lists the variants
+    /// in the order they are declared.
+    fn index(&self) -> usize {
+        *self as usize
+    }
+
+    /// A symmetry is determined by where it takes `(1, 2, 3)`.
+    fn image(&self) -> (i8, i8, i8) {
+        IMAGES[self.index()]
+    }
+
+    /// The symmetry that takes `(1, 2, 3)` to `image`, if any.
+    fn with_image(image: (i8, i8, i8)) -> Option<CubeSymmetry> {
+        match image {
+            (1, 2, 3) => Some(CubeSymmetry::E0123),
+            (-3, -2, -1) => Some(CubeSymmetry::E0132),
+            (-1, -3, -2) => Some(CubeSymmetry::E0213),
+            (2, 3, 1) => Some(CubeSymmetry::E0231),
+            (3, 1, 2) => Some(CubeSymmetry::E0312),
+            (-2, -1, -3) => Some(CubeSymmetry::E0321),
+            (3, -2, 1) => Some(CubeSymmetry::E1023),
+            (-1, 2, -3) => Some(CubeSymmetry::E1032),
+            (-3, -1, 2) => Some(CubeSymmetry::E1203),
+            (-2, 1, 3) => Some(CubeSymmetry::E1230),
+            (1, 3, -2) => Some(CubeSymmetry::E1302),
+            (2, -3, -1) => Some(CubeSymmetry::E1320),
+            (-2, 3, -1) => Some(CubeSymmetry::E2013),
+            (1, -3, 2) => Some(CubeSymmetry::E2031),
+            (2, 1, -3) => Some(CubeSymmetry::E2103),
+            (3, -1, -2) => Some(CubeSymmetry::E2130),
+            (-1, -2, 3) => Some(CubeSymmetry::E2301),
+            (-3, 2, 1) => Some(CubeSymmetry::E2310),
+            (2, -1, 3) => Some(CubeSymmetry::E3012),
+            (-3, 1, -2) => Some(CubeSymmetry::E3021),
+            (-2, -3, 1) => Some(CubeSymmetry::E3102),
+            (-1, 3, 2) => Some(CubeSymmetry::E3120),
+            (3, 2, -1) => Some(CubeSymmetry::E3201),
+            (1, -2, -3) => Some(CubeSymmetry::E3210),
+            (-1, -2, -3) => Some(CubeSymmetry::R0123),
+            (3, 2, 1) => Some(CubeSymmetry::R0132),
+            (1, 3, 2) => Some(CubeSymmetry::R0213),
+            (-2, -3, -1) => Some(CubeSymmetry::R0231),
+            (-3, -1, -2) => Some(CubeSymmetry::R0312),
+            (2, 1, 3) => Some(CubeSymmetry::R0321),
+            (-3, 2, -1) => Some(CubeSymmetry::R1023),
+            (1, -2, 3) => Some(CubeSymmetry::R1032),
+            (3, 1, -2) => Some(CubeSymmetry::R1203),
+            (2, -1, -3) => Some(CubeSymmetry::R1230),
+            (-1, -3, 2) => Some(CubeSymmetry::R1302),
+            (-2, 3, 1) => Some(CubeSymmetry::R1320),
+            (2, -3, 1) => Some(CubeSymmetry::R2013),
+            (-1, 3, -2) => Some(CubeSymmetry::R2031),
+            (-2, -1, 3) => Some(CubeSymmetry::R2103),
+            (-3, 1, 2) => Some(CubeSymmetry::R2130),
+            (1, 2, -3) => Some(CubeSymmetry::R2301),
+            (3, -2, -1) => Some(CubeSymmetry::R2310),
+            (-2, 1, -3) => Some(CubeSymmetry::R3012),
+            (3, -1, 2) => Some(CubeSymmetry::R3021),
+            (2, 3, -1) => Some(CubeSymmetry::R3102),
+            (1, -3, -2) => Some(CubeSymmetry::R3120),
+            (-3, -2, 1) => Some(CubeSymmetry::R3201),
+            (-1, 2, 3) => Some(CubeSymmetry::R3210),
+            _ => None,
+        }
+    }
+}
+
+/// Shows where the symmetry takes the coordinates, e.g. `E0132: (x, y, z) ->
+/// (-z, -y, -x)`.
+impl Display for CubeSymmetry {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        let (a, b, c) = self.image();
+        let axis = |coordinate: i8| {
+            let sign = if coordinate < 0 { "-" } else { "" };
+            let name = match coordinate.abs() {
+                1 => "x",
+                2 => "y",
+                _ => "z",
+            };
+            format!("{}{}", sign, name)
+        };
+        write!(f, "{:?}: (x, y, z) -> ({}, {}, {})", self, axis(a), axis(b), axis(c))
+    }
 }
 
 /// Iterator over the symmetries of the cube.
 pub struct CubeSymmetryIterator {
-    item: Option<CubeSymmetry>,
+    index: usize,
+    end: usize,
 }
 
 impl CubeSymmetryIterator {
-    /// Create a `CubeSymmetryIterator`.
+    /// Create a `CubeSymmetryIterator` over the 24 rotations of the cube.
+    #[deprecated(note = "use `CubeSymmetryIterator::rotations_only`, which says what it iterates over")]
     pub fn new() -> CubeSymmetryIterator {
-        CubeSymmetryIterator { item: Some(CubeSymmetry::E0123) }
+        CubeSymmetryIterator::rotations_only()
+    }
+
+    /// Create a `CubeSymmetryIterator` over the 24 rotations of the cube, i.e.
+    /// the orientation preserving symmetries. Physical pieces can only be
+    /// rotated, not reflected.
+    pub fn rotations_only() -> CubeSymmetryIterator {
+        CubeSymmetryIterator { index: 0, end: 24 }
+    }
+
+    /// Create a `CubeSymmetryIterator` over the identity only, the symmetry
+    /// that leaves everything in place.
+    pub fn identity_only() -> CubeSymmetryIterator {
+        CubeSymmetryIterator { index: 0, end: 1 }
+    }
+
+    /// Create a `CubeSymmetryIterator` over all 48 symmetries of the cube,
+    /// i.e. the rotations followed by the reflections.
+    pub fn with_reflections() -> CubeSymmetryIterator {
+        CubeSymmetryIterator { index: 0, end: SYMMETRIES.len() }
+    }
+}
+
+impl Default for CubeSymmetryIterator {
+    fn default() -> Self {
+        Self::rotations_only()
     }
 }
 
@@ -81,78 +329,165 @@ impl Iterator for CubeSymmetryIterator {
     type Item = CubeSymmetry;
 
     fn next(&mut self) -> Option<Self::Item> {
-        match self.item.clone() {
-            s @ Some(CubeSymmetry::E0123) => { self.item = Some(CubeSymmetry::E0132); s },
-            s @ Some(CubeSymmetry::E0132) => { self.item = Some(CubeSymmetry::E0213); s },
-            s @ Some(CubeSymmetry::E0213) => { self.item = Some(CubeSymmetry::E0231); s },
-            s @ Some(CubeSymmetry::E0231) => { self.item = Some(CubeSymmetry::E0312); s },
-            s @ Some(CubeSymmetry::E0312) => { self.item = Some(CubeSymmetry::E0321); s },
-            s @ Some(CubeSymmetry::E0321) => { self.item = Some(CubeSymmetry::E1023); s },
-
-            s @ Some(CubeSymmetry::E1023) => { self.item = Some(CubeSymmetry::E1032); s },
-            s @ Some(CubeSymmetry::E1032) => { self.item = Some(CubeSymmetry::E1203); s },
-            s @ Some(CubeSymmetry::E1203) => { self.item = Some(CubeSymmetry::E1230); s },
-            s @ Some(CubeSymmetry::E1230) => { self.item = Some(CubeSymmetry::E1302); s },
-            s @ Some(CubeSymmetry::E1302) => { self.item = Some(CubeSymmetry::E1320); s },
-            s @ Some(CubeSymmetry::E1320) => { self.item = Some(CubeSymmetry::E2013); s },
-
-            s @ Some(CubeSymmetry::E2013) => { self.item = Some(CubeSymmetry::E2031); s },
-            s @ Some(CubeSymmetry::E2031) => { self.item = Some(CubeSymmetry::E2103); s },
-            s @ Some(CubeSymmetry::E2103) => { self.item = Some(CubeSymmetry::E2130); s },
-            s @ Some(CubeSymmetry::E2130) => { self.item = Some(CubeSymmetry::E2301); s },
-            s @ Some(CubeSymmetry::E2301) => { self.item = Some(CubeSymmetry::E2310); s },
-            s @ Some(CubeSymmetry::E2310) => { self.item = Some(CubeSymmetry::E3012); s },
-
-            s @ Some(CubeSymmetry::E3012) => { self.item = Some(CubeSymmetry::E3021); s },
-            s @ Some(CubeSymmetry::E3021) => { self.item = Some(CubeSymmetry::E3102); s },
-            s @ Some(CubeSymmetry::E3102) => { self.item = Some(CubeSymmetry::E3120); s },
-            s @ Some(CubeSymmetry::E3120) => { self.item = Some(CubeSymmetry::E3201); s },
-            s @ Some(CubeSymmetry::E3201) => { self.item = Some(CubeSymmetry::E3210); s },
-            s @ Some(CubeSymmetry::E3210) | s @ None => { self.item = None; s },
+        if self.index < self.end {
+            let symmetry = SYMMETRIES[self.index];
+            self.index += 1;
+            Some(symmetry)
+        } else {
+            None
         }
     }
 }
 
-impl Transformable for (i8, i8, i8) {
-    fn transform(&mut self, symmetry: &CubeSymmetry) {
-        let x = self.0;
-        let y = self.1;
-        let z = self.2;
-        let sx: i8;
-        let sy: i8;
-        let sz: i8;
-        match *symmetry {
-            CubeSymmetry::E0123 => { sx =  x; sy =  y; sz =  z; },
-            CubeSymmetry::E0132 => { sx = -z; sy = -y; sz = -x; },
-            CubeSymmetry::E0213 => { sx = -x; sy = -z; sz = -y; },
-            CubeSymmetry::E0231 => { sx =  y; sy =  z; sz =  x; },
-            CubeSymmetry::E0312 => { sx =  z; sy =  x; sz =  y; },
-            CubeSymmetry::E0321 => { sx = -y; sy = -x; sz = -z; },
-            CubeSymmetry::E1023 => { sx =  z; sy = -y; sz =  x; },
-            CubeSymmetry::E1032 => { sx = -x; sy =  y; sz = -z; },
-            CubeSymmetry::E1203 => { sx = -z; sy = -x; sz =  y; },
-            CubeSymmetry::E1230 => { sx = -y; sy =  x; sz =  z; },
-            CubeSymmetry::E1302 => { sx =  x; sy =  z; sz = -y; },
-            CubeSymmetry::E1320 => { sx =  y; sy = -z; sz = -x; },
-            CubeSymmetry::E2013 => { sx = -y; sy =  z; sz = -x; },
-            CubeSymmetry::E2031 => { sx =  x; sy = -z; sz =  y; },
-            CubeSymmetry::E2103 => { sx =  y; sy =  x; sz = -z; },
-            CubeSymmetry::E2130 => { sx =  z; sy = -x; sz = -y; },
-            CubeSymmetry::E2301 => { sx = -x; sy = -y; sz =  z; },
-            CubeSymmetry::E2310 => { sx = -z; sy =  y; sz =  x; },
-            CubeSymmetry::E3012 => { sx =  y; sy = -x; sz =  z; },
-            CubeSymmetry::E3021 => { sx = -z; sy =  x; sz = -y; },
-            CubeSymmetry::E3102 => { sx = -y; sy = -z; sz =  x; },
-            CubeSymmetry::E3120 => { sx = -x; sy =  z; sz =  y; },
-            CubeSymmetry::E3201 => { sx =  z; sy =  y; sz = -x; },
-            CubeSymmetry::E3210 => { sx =  x; sy = -y; sz = -z; },
-         }
-        self.0 = sx;
-        self.1 = sy;
-        self.2 = sz;
+/// Symmetries of the square, the dihedral group of order 8. These orient
+/// entities in two dimensions, which are turned and flipped over within the
+/// plane.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum SquareSymmetry {
+    /// `(x, y) -> (x, y)`
+    Identity,
+    /// `(x, y) -> (-y, x)`
+    Rotate90,
+    /// `(x, y) -> (-x, -y)`
+    Rotate180,
+    /// `(x, y) -> (y, -x)`
+    Rotate270,
+    /// `(x, y) -> (-x, y)`
+    ReflectX,
+    /// `(x, y) -> (x, -y)`
+    ReflectY,
+    /// `(x, y) -> (y, x)`
+    ReflectDiagonal,
+    /// `(x, y) -> (-y, -x)`
+    ReflectAntiDiagonal,
+}
+
+const SQUARE_SYMMETRIES: [SquareSymmetry; 8] = [
+    SquareSymmetry::Identity, SquareSymmetry::Rotate90, SquareSymmetry::Rotate180, SquareSymmetry::Rotate270,
+    SquareSymmetry::ReflectX, SquareSymmetry::ReflectY, SquareSymmetry::ReflectDiagonal, SquareSymmetry::ReflectAntiDiagonal,
+];
+
+impl SquareSymmetry {
+    /// All 8 symmetries of the square, the 4 rotations followed by the 4
+    /// reflections.
+    pub fn all() -> &'static [SquareSymmetry] {
+        &SQUARE_SYMMETRIES
+    }
+
+    /// Determine if this symmetry is a rotation within the plane.
+    pub fn is_proper(&self) -> bool {
+        SQUARE_SYMMETRIES[..4].contains(self)
+    }
+
+    /// The rotation of the cube that keeps the plane `z = 0` in place and acts
+    /// on it like this symmetry. A reflection of the square is a half turn of
+    /// the cube that flips the plane over.
+    pub fn cube_symmetry(&self) -> CubeSymmetry {
+        match self {
+            SquareSymmetry::Identity => CubeSymmetry::E0123,
+            SquareSymmetry::Rotate90 => CubeSymmetry::E1230,
+            SquareSymmetry::Rotate180 => CubeSymmetry::E2301,
+            SquareSymmetry::Rotate270 => CubeSymmetry::E3012,
+            SquareSymmetry::ReflectX => CubeSymmetry::E1032,
+            SquareSymmetry::ReflectY => CubeSymmetry::E3210,
+            SquareSymmetry::ReflectDiagonal => CubeSymmetry::E2103,
+            SquareSymmetry::ReflectAntiDiagonal => CubeSymmetry::E0321,
+        }
+    }
+}
+
+/// Iterator over the symmetries of the square.
+pub struct SquareSymmetryIterator {
+    index: usize,
+    end: usize,
+}
+
+impl SquareSymmetryIterator {
+    /// Create a `SquareSymmetryIterator` over all 8 symmetries of the square.
+    pub fn new() -> SquareSymmetryIterator {
+        SquareSymmetryIterator { index: 0, end: SQUARE_SYMMETRIES.len() }
+    }
+
+    /// Create a `SquareSymmetryIterator` over the 4 rotations of the square.
+    pub fn rotations_only() -> SquareSymmetryIterator {
+        SquareSymmetryIterator { index: 0, end: 4 }
+    }
+}
+
+impl Default for SquareSymmetryIterator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Iterator for SquareSymmetryIterator {
+    type Item = SquareSymmetry;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index < self.end {
+            let symmetry = SQUARE_SYMMETRIES[self.index];
+            self.index += 1;
+            Some(symmetry)
+        } else {
+            None
+        }
     }
 }
 
+/// Implement `Transformable` for triples of a signed integer type.
+macro_rules! transformable_triple {
+    ($($coordinate:ty),*) => {$(
+        impl Transformable for ($coordinate, $coordinate, $coordinate) {
+            fn transform(&mut self, symmetry: &CubeSymmetry) {
+                let x = self.0;
+                let y = self.1;
+                let z = self.2;
+                let sx: $coordinate;
+                let sy: $coordinate;
+                let sz: $coordinate;
+                match symmetry.rotation() {
+                    CubeSymmetry::E0123 => { sx =  x; sy =  y; sz =  z; },
+                    CubeSymmetry::E0132 => { sx = -z; sy = -y; sz = -x; },
+                    CubeSymmetry::E0213 => { sx = -x; sy = -z; sz = -y; },
+                    CubeSymmetry::E0231 => { sx =  y; sy =  z; sz =  x; },
+                    CubeSymmetry::E0312 => { sx =  z; sy =  x; sz =  y; },
+                    CubeSymmetry::E0321 => { sx = -y; sy = -x; sz = -z; },
+                    CubeSymmetry::E1023 => { sx =  z; sy = -y; sz =  x; },
+                    CubeSymmetry::E1032 => { sx = -x; sy =  y; sz = -z; },
+                    CubeSymmetry::E1203 => { sx = -z; sy = -x; sz =  y; },
+                    CubeSymmetry::E1230 => { sx = -y; sy =  x; sz =  z; },
+                    CubeSymmetry::E1302 => { sx =  x; sy =  z; sz = -y; },
+                    CubeSymmetry::E1320 => { sx =  y; sy = -z; sz = -x; },
+                    CubeSymmetry::E2013 => { sx = -y; sy =  z; sz = -x; },
+                    CubeSymmetry::E2031 => { sx =  x; sy = -z; sz =  y; },
+                    CubeSymmetry::E2103 => { sx =  y; sy =  x; sz = -z; },
+                    CubeSymmetry::E2130 => { sx =  z; sy = -x; sz = -y; },
+                    CubeSymmetry::E2301 => { sx = -x; sy = -y; sz =  z; },
+                    CubeSymmetry::E2310 => { sx = -z; sy =  y; sz =  x; },
+                    CubeSymmetry::E3012 => { sx =  y; sy = -x; sz =  z; },
+                    CubeSymmetry::E3021 => { sx = -z; sy =  x; sz = -y; },
+                    CubeSymmetry::E3102 => { sx = -y; sy = -z; sz =  x; },
+                    CubeSymmetry::E3120 => { sx = -x; sy =  z; sz =  y; },
+                    CubeSymmetry::E3201 => { sx =  z; sy =  y; sz = -x; },
+                    CubeSymmetry::E3210 => { sx =  x; sy = -y; sz = -z; },
+                    _ => unreachable!("rotation part is always a rotation"),
+                }
+                if symmetry.is_proper() {
+                    self.0 = sx;
+                    self.1 = sy;
+                    self.2 = sz;
+                } else {
+                    self.0 = -sx;
+                    self.1 = -sy;
+                    self.2 = -sz;
+                }
+            }
+        }
+    )*}
+}
+
+transformable_triple!(i8, i16, i32);
+
 impl Transformable for (i8, i8) {
     fn transform(&mut self, symmetry: &CubeSymmetry) {
         let mut v: (i8, i8, i8) = (self.0, self.1, 0);
@@ -160,4 +495,110 @@ impl Transformable for (i8, i8) {
         self.0 = v.0;
         self.1 = v.1;
     }
+
+    /// The symmetries of the square, whether or not `reflections` are asked
+    /// for. A flat entity can always be flipped over.
+    fn orienting_symmetries(_reflections: bool) -> Vec<CubeSymmetry> {
+        SquareSymmetryIterator::new().map(|symmetry| symmetry.cube_symmetry()).collect()
+    }
+
+    /// Swaps x and y.
+    fn reversing_symmetry() -> CubeSymmetry {
+        CubeSymmetry::R0321
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn all_should_list_distinct_symmetries() {
+        let all = CubeSymmetry::all();
+
+        assert_eq!(all.len(), 48);
+        for (index, symmetry) in all.iter().enumerate() {
+            assert!(!all[index + 1..].contains(symmetry));
+        }
+        assert_eq!(format!("{}", CubeSymmetry::E0123), "E0123: (x, y, z) -> (x, y, z)");
+        assert_eq!(format!("{}", CubeSymmetry::E0132), "E0132: (x, y, z) -> (-z, -y, -x)");
+        assert_eq!(format!("{}", CubeSymmetry::R0123), "R0123: (x, y, z) -> (-x, -y, -z)");
+    }
+
+    #[test]
+    fn square_symmetries_should_act_within_the_plane() {
+        let images: Vec<(i8, i8)> = SquareSymmetryIterator::new()
+            .map(|symmetry| {
+                let mut point: (i8, i8) = (1, 2);
+                point.transform(&symmetry.cube_symmetry());
+                point
+            })
+            .collect();
+
+        assert_eq!(images, vec!((1, 2), (-2, 1), (-1, -2), (2, -1), (-1, 2), (1, -2), (2, 1), (-2, -1)));
+        assert_eq!(SquareSymmetryIterator::rotations_only().count(), 4);
+        assert!(SquareSymmetryIterator::rotations_only().all(|symmetry| symmetry.is_proper()));
+        assert!(SquareSymmetry::all().iter().all(|symmetry| symmetry.cube_symmetry().is_proper()));
+    }
+
+    #[test]
+    fn images_should_agree_with_transform() {
+        for (index, symmetry) in CubeSymmetry::all().iter().enumerate() {
+            let mut image: (i8, i8, i8) = (1, 2, 3);
+            image.transform(symmetry);
+
+            assert_eq!(symmetry.index(), index);
+            assert_eq!(symmetry.image(), image);
+            assert_eq!(CubeSymmetry::with_image(image), Some(*symmetry));
+        }
+        assert_eq!(CubeSymmetry::with_image((1, 1, 3)), None);
+    }
+
+    #[test]
+    fn iterators_should_yield_rotations_or_all_symmetries() {
+        assert_eq!(CubeSymmetryIterator::rotations_only().count(), 24);
+        assert!(CubeSymmetryIterator::rotations_only().all(|symmetry| symmetry.is_proper()));
+        assert!(CubeSymmetryIterator::rotations_only().all(|symmetry| symmetry.is_proper()));
+        assert_eq!(CubeSymmetryIterator::with_reflections().count(), 48);
+        assert_eq!(CubeSymmetryIterator::with_reflections().filter(|symmetry| !symmetry.is_proper()).count(), 24);
+    }
+
+    #[test]
+    fn reflections_should_reverse_rotations() {
+        let mut rotated: (i8, i8, i8) = (1, 2, 3);
+        let mut reflected: (i8, i8, i8) = (1, 2, 3);
+
+        rotated.transform(&CubeSymmetry::E0231);
+        reflected.transform(&CubeSymmetry::R0231);
+
+        assert_eq!(rotated, (2, 3, 1));
+        assert_eq!(reflected, (-2, -3, -1));
+    }
+
+    #[test]
+    fn composition_should_apply_other_symmetry_first() {
+        for first in CubeSymmetryIterator::with_reflections() {
+            for second in CubeSymmetryIterator::with_reflections() {
+                let mut expected: (i8, i8, i8) = (3, -1, 2);
+                expected.transform(&first);
+                expected.transform(&second);
+                let mut composed: (i8, i8, i8) = (3, -1, 2);
+                composed.transform(&second.compose(&first));
+
+                assert_eq!(composed, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn inverse_should_undo_symmetry() {
+        for symmetry in CubeSymmetryIterator::with_reflections() {
+            let mut position: (i8, i8, i8) = (3, -1, 2);
+            position.transform(&symmetry);
+            position.transform(&symmetry.inverse());
+
+            assert_eq!(position, (3, -1, 2));
+            assert_eq!(symmetry.compose(&symmetry.inverse()), CubeSymmetry::E0123);
+        }
+    }
 }
@@ -1,28 +1,145 @@
 //! template is a container to hold orientations of pieces.
 
-use std::convert::From;
+use std::convert::{From, TryFrom};
+use super::super::solver::DimensionError;
 use super::super::vector::VectorAdd;
-use super::{Position, Normalizable, Piece, CubeSymmetryIterator, Translatable, Transformable, MinimumPosition};
+use super::{Rgb, Position, Normalizable, Piece, CubeSymmetry, CubeSymmetryIterator, Translatable, Transformable, MinimumPosition};
 
 /// A `Template` is a container to hold a representation of a `Piece`. By
 /// Iterating over a one gets a piece in all the possible orientations.
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Template<T> {
     positions: Vec<Position<T>>,
-    name: Option<String>
+    name: Option<String>,
+    color: Option<Rgb>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    orientations: Orientations,
+}
+
+/// The orientations a `Template` can be placed in.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Orientations {
+    /// Only the rotations of the cube are applied.
+    #[default]
+    Rotations,
+    /// Rotations are applied and the `Template` may be flipped over into its
+    /// mirror image.
+    RotationsAndReflections,
+    /// The `Template` is placed in the orientation it was created with only.
+    Fixed,
 }
 
 impl<T> Template<T> {
     /// Create a `Template` from a vector of `Position`s.
     pub fn new(positions: Vec<Position<T>>) -> Template<T> {
-        Template { positions, name: None }
+        Template { positions, name: None, color: None, orientations: Orientations::Rotations }
+    }
+
+    /// Create a `Template` from a vector of `Position`s that is neither rotated
+    /// nor reflected, i.e. it is placed in this orientation only.
+    pub fn fixed(positions: Vec<Position<T>>) -> Template<T> {
+        Template { positions, name: None, color: None, orientations: Orientations::Fixed }
+    }
+
+    /// The cells of this `Template`, in the orientation it was created with.
+    pub fn cells(&self) -> &[Position<T>] {
+        &self.positions
+    }
+
+    /// The number of cells of this `Template`.
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// Determine if this `Template` has no cells.
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
     }
 
     /// Create a named `Template` from this `Template`
     pub fn with_name<S>(self, name: S) -> Template<T> where S : Into<String> {
         let name = Some(name.into());
 
-        Template { positions: self.positions, name }
+        Template { name, ..self }
+    }
+
+    /// Create a colored `Template` from this `Template`. All the `Piece`s
+    /// created from it will carry the color.
+    pub fn with_color(self, color: Rgb) -> Template<T> {
+        Template { color: Some(color), ..self }
+    }
+
+    /// Create a `Template` from this `Template` that also yields the mirror
+    /// images of its orientations.
+    pub fn with_reflections(self) -> Template<T> {
+        Template { orientations: Orientations::RotationsAndReflections, ..self }
+    }
+}
+
+impl Template<(i8, i8)> {
+    /// Create a `Template` from a drawing. Every character that is not a space
+    /// marks an occupied cell, with the column as x and the row as y. Leading
+    /// and trailing blank lines are ignored. Fails when a row or a column of an
+    /// occupied cell would not fit in an `i8`.
+    pub fn from_ascii(input: &str) -> Result<Template<(i8, i8)>, DimensionError> {
+        let lines: Vec<&str> = input.lines().collect();
+        let is_blank = |line: &&str| line.trim().is_empty();
+        let start = lines.iter().position(|line| !is_blank(line)).unwrap_or(lines.len());
+        let end = lines.iter().rposition(|line| !is_blank(line)).map_or(start, |index| index + 1);
+
+        let mut positions: Vec<Position<(i8, i8)>> = vec!();
+        for (y, line) in lines[start..end].iter().enumerate() {
+            for (x, character) in line.chars().enumerate() {
+                if !character.is_whitespace() {
+                    let x = i8::try_from(x).map_err(|_| DimensionError::TooLarge(x + 1))?;
+                    let y = i8::try_from(y).map_err(|_| DimensionError::TooLarge(y + 1))?;
+                    positions.push(Position::d2(x, y));
+                }
+            }
+        }
+        Ok(Template::new(positions))
+    }
+}
+
+impl<T> Template<T> where T: Clone + PartialOrd + Ord + Transformable + Normalizable<T> + VectorAdd<T> {
+    /// The number of distinct orientations of this `Template`. The more
+    /// symmetric a `Template` is, the fewer orientations it has.
+    pub fn orientation_count(&self) -> usize {
+        self.into_iter().count()
+    }
+
+    /// Iterate over the orientations of this `Template` that can be reached by
+    /// rotations only, even when the `Template` allows reflections. A fixed
+    /// `Template` has only its own orientation.
+    pub fn proper_orientations(&self) -> PieceIterator<T> {
+        let symmetries = match self.orientations {
+            Orientations::Fixed => CubeSymmetryIterator::identity_only().collect(),
+            _ => T::orienting_symmetries(false),
+        };
+        PieceIterator::with_symmetries(self.clone(), symmetries)
+    }
+
+    /// Determine if this `Template` is chiral, i.e. if its mirror image can not
+    /// be reached by rotating it. This is a property of the shape, so the
+    /// orientations the `Template` is placed in are ignored. A flat `Template`
+    /// is never chiral, because it can be flipped over.
+    pub fn is_chiral(&self) -> bool {
+        let proper = PieceIterator::with_symmetries(self.clone(), T::orienting_symmetries(false)).count();
+        let all = PieceIterator::with_symmetries(self.clone(), T::orienting_symmetries(true)).count();
+        proper < all
+    }
+
+    /// Determine if this `Template` is a rotated or translated copy of `other`,
+    /// that can be placed in the same orientations. Names and colors are
+    /// ignored.
+    pub fn same_shape(&self, other: &Template<T>) -> bool {
+        let form = |template: &Template<T>| match template.orientations {
+            Orientations::Fixed => Piece::new(template.positions.clone()).normalized(),
+            _ => Piece::new(template.positions.clone()).canonical(),
+        };
+        self.orientations == other.orientations && form(self).same_shape(&form(other))
     }
 }
 
@@ -35,65 +152,115 @@ impl<T> IntoIterator for Template<T> where T: Clone + PartialOrd + Ord + Transfo
     }
 }
 
+/// Iterates over the orientations of a borrowed `Template`, which is cloned
+/// into the `PieceIterator`.
+impl<T> IntoIterator for &Template<T> where T: Clone + PartialOrd + Ord + Transformable + Normalizable<T> + VectorAdd<T> {
+    type Item = Piece<T>;
+    type IntoIter = PieceIterator<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        PieceIterator::new(self.clone())
+    }
+}
 
 /// The `PieceIterator` will return `Piece`s  in all the orientations possible
-/// from a `Template`
+/// from a `Template`. A `Template` in three dimensions is oriented by the
+/// symmetries of the cube, one in two dimensions by the symmetries of the
+/// square.
 pub struct PieceIterator<T> {
-    symmetry_iterator: CubeSymmetryIterator,
+    symmetry_iterator: std::vec::IntoIter<CubeSymmetry>,
     seen_pieces: Vec<Piece<T>>,
     template: Template<T>,
 }
 
 impl<T> PieceIterator<T> {
     /// Creates a `PieceIterator` for the `Template` that is passed as an argument
-    pub fn new(template: Template<T>) -> PieceIterator<T> {
+    pub fn new(template: Template<T>) -> PieceIterator<T> where T: Transformable {
+        let symmetries = match template.orientations {
+            Orientations::Rotations => T::orienting_symmetries(false),
+            Orientations::RotationsAndReflections => T::orienting_symmetries(true),
+            Orientations::Fixed => CubeSymmetryIterator::identity_only().collect(),
+        };
+        PieceIterator::with_symmetries(template, symmetries)
+    }
+
+    fn with_symmetries(template: Template<T>, symmetries: Vec<CubeSymmetry>) -> PieceIterator<T> {
         PieceIterator {
-            symmetry_iterator: CubeSymmetryIterator::new(),
+            symmetry_iterator: symmetries.into_iter(),
             seen_pieces: vec!(),
             template,
         }
     }
 }
 
-impl<T> Iterator for PieceIterator<T> where T: Clone + PartialEq + Eq + PartialOrd + Ord + Transformable + Normalizable<T> + VectorAdd<T> {
-    type Item = Piece<T>;
-
-    fn next(&mut self) -> Option<Self::Item> {
+impl<T> PieceIterator<T> where T: Clone + PartialEq + Eq + PartialOrd + Ord + Transformable + Normalizable<T> + VectorAdd<T> {
+    /// Return the next orientation together with the `CubeSymmetry` that
+    /// produced it from the `Template`.
+    pub fn next_with_symmetry(&mut self) -> Option<(CubeSymmetry, Piece<T>)> {
         let mut symmetry_option = self.symmetry_iterator.next();
-        while symmetry_option.is_some() {
-            let piece_option = symmetry_option.map(|symmetry|{
-                let mut piece = Piece::from(self.template.clone());
+        while let Some(symmetry) = symmetry_option {
+            let mut piece = Piece::from(self.template.clone());
 
-                piece.transform(&symmetry);
-                let minimum_position = piece.minimum_position();
-                let translation = minimum_position.unwrap().to_reference();
-                piece.translate(&translation);
+            piece.transform(&symmetry);
+            let minimum_position = piece.minimum_position();
+            let translation = minimum_position.unwrap().to_reference();
+            piece.translate(&translation);
 
-                piece
-            });
+            let clone = piece.clone();
+            if !self.seen_pieces.contains(&clone) {
+                self.seen_pieces.push(clone);
 
-            if piece_option.is_some() {
-                let piece = piece_option.unwrap();
-                let clone = piece.clone();
-                if !self.seen_pieces.contains(&clone) {
-                    self.seen_pieces.push(clone);
-
-                    return Some(piece)
-                }
+                return Some((symmetry, piece))
             }
 
             symmetry_option = self.symmetry_iterator.next();
         }
         None
     }
+
+    /// Collect the remaining orientations, each together with the
+    /// `CubeSymmetry` that produced it from the `Template`.
+    pub fn collect_with_symmetries(mut self) -> Vec<(CubeSymmetry, Piece<T>)> {
+        let mut orientations = vec!();
+        while let Some(orientation) = self.next_with_symmetry() {
+            orientations.push(orientation);
+        }
+        orientations
+    }
+}
+
+impl<T> Iterator for PieceIterator<T> where T: Clone + PartialEq + Eq + PartialOrd + Ord + Transformable + Normalizable<T> + VectorAdd<T> {
+    type Item = Piece<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_with_symmetry().map(|(_, piece)| piece)
+    }
 }
 
 impl<T> From<Template<T>> for Piece<T> where T: Clone + PartialOrd + Ord + Transformable + Normalizable<T> + VectorAdd<T> {
     fn from(template: Template<T>) -> Self {
-        if template.name.is_some() {
-            Piece::named(template.positions, template.name.unwrap())
-        } else {
-            Piece::new(template.positions)
+        let piece = match template.name {
+            Some(name) => Piece::named(template.positions, name),
+            None => Piece::new(template.positions),
+        };
+        match template.color {
+            Some(color) => piece.with_color(color),
+            None => piece,
+        }
+    }
+}
+
+/// Wraps the cells, name and color of a `Piece` into a `Template`.
+impl<T> From<Piece<T>> for Template<T> where T: Clone + PartialOrd + Ord {
+    fn from(piece: Piece<T>) -> Self {
+        let template = Template::new(piece.positions().to_vec());
+        let template = match piece.name() {
+            Some(name) => template.with_name(name),
+            None => template,
+        };
+        match piece.color() {
+            Some(color) => template.with_color(color),
+            None => template,
         }
     }
 }
@@ -103,6 +270,57 @@ mod tests {
     use std::iter::Iterator;
     use super::*;
 
+    #[test]
+    fn template_should_expose_its_cells() {
+        let cells = vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, 0, 0),
+            Position::new(2, 0, 0),
+            Position::new(3, 0, 0),
+            Position::new(1, 1, 0),
+        );
+        let pentomino = Template::new(cells.clone());
+
+        assert_eq!(pentomino.len(), 5);
+        assert_eq!(pentomino.cells(), &cells[..]);
+    }
+
+    #[test]
+    fn piece_should_round_trip_through_template() {
+        let piece = Piece::named(vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, 0, 0),
+            Position::new(1, 1, 0),
+        ), "L").with_color(Rgb::new(1, 2, 3));
+
+        let template = Template::from(piece.clone());
+
+        assert_eq!(template.cells(), piece.positions());
+        assert_eq!(Piece::from(template), piece);
+    }
+
+    #[test]
+    fn same_shape_should_ignore_rotation_and_name() {
+        let template = Template::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, 0, 0),
+            Position::new(1, 1, 0),
+        ));
+        let rotated = Template::new(vec!(
+            Position::new(5, 5, 5),
+            Position::new(5, 6, 5),
+            Position::new(4, 6, 5),
+        )).with_name("L");
+
+        assert!(template.same_shape(&rotated));
+        assert!(!template.same_shape(&rotated.clone().with_reflections()));
+        assert!(!template.same_shape(&Template::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, 0, 0),
+            Position::new(2, 0, 0),
+        ))));
+    }
+
     #[test]
     fn templates_are_equal_on_values() {
         let a = Template::new(vec!(
@@ -146,4 +364,219 @@ mod tests {
 
         assert_eq!(iterator.count(), 3);
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn templates_should_round_trip_through_json() {
+        let template = Template::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, 0, 0),
+        )).with_name("domino");
+
+        let json = serde_json::to_string(&template).expect("to serialize");
+        let copy: Template<(i8, i8, i8)> = serde_json::from_str(&json).expect("to deserialize");
+
+        assert_eq!(copy, template);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn templates_should_deserialize_from_positions_and_name() {
+        let json = "{\"positions\":[[0,0,0],[0,1,0]],\"name\":\"domino\"}";
+
+        let template: Template<(i8, i8, i8)> = serde_json::from_str(json).expect("to deserialize");
+
+        assert_eq!(template, Template::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(0, 1, 0),
+        )).with_name("domino"));
+    }
+
+    #[test]
+    fn templates_should_be_parsed_from_ascii() {
+        let template = Template::from_ascii("\n#\n##\n #\n\n");
+
+        assert_eq!(template, Ok(Template::new(vec!(
+            Position::d2(0, 0),
+            Position::d2(0, 1),
+            Position::d2(1, 1),
+            Position::d2(1, 2),
+        ))));
+    }
+
+    #[test]
+    fn templates_should_be_parsed_from_ascii_with_ragged_rows() {
+        let template = Template::from_ascii("###\n#\n  #");
+
+        assert_eq!(template, Ok(Template::new(vec!(
+            Position::d2(0, 0),
+            Position::d2(1, 0),
+            Position::d2(2, 0),
+            Position::d2(0, 1),
+            Position::d2(2, 2),
+        ))));
+    }
+
+    #[test]
+    fn templates_should_not_be_parsed_from_ascii_beyond_the_coordinate_range() {
+        let wide = format!("{}#", " ".repeat(128));
+        let tall = "#\n".repeat(129);
+
+        assert_eq!(Template::from_ascii(&wide), Err(DimensionError::TooLarge(129)));
+        assert_eq!(Template::from_ascii(&tall), Err(DimensionError::TooLarge(129)));
+        assert!(Template::from_ascii(&format!("{}#", " ".repeat(127))).is_ok());
+    }
+
+    #[test]
+    fn flat_templates_should_only_be_oriented_in_the_plane() {
+        let domino = Template::new(vec!(
+            Position::d2(0, 0),
+            Position::d2(1, 0),
+        ));
+        let l_tromino = Template::new(vec!(
+            Position::d2(0, 0),
+            Position::d2(1, 0),
+            Position::d2(0, 1),
+        ));
+
+        assert_eq!(domino.orientation_count(), 2);
+        assert_eq!(l_tromino.orientation_count(), 4);
+        assert!(l_tromino.into_iter().all(|piece| piece.len() == 3));
+    }
+
+    #[test]
+    fn polyominoes_should_have_up_to_8_orientations() {
+        let tetromino = |cells: [(i8, i8); 4]| Template::new(cells.iter().map(|(x, y)| Position::d2(*x, *y)).collect());
+
+        assert_eq!(tetromino([(0, 0), (1, 0), (2, 0), (0, 1)]).orientation_count(), 8);
+        assert_eq!(tetromino([(0, 0), (1, 0), (0, 1), (1, 1)]).orientation_count(), 1);
+        assert_eq!(tetromino([(0, 0), (1, 0), (2, 0), (3, 0)]).orientation_count(), 2);
+        assert_eq!(tetromino([(0, 0), (1, 0), (2, 0), (1, 1)]).orientation_count(), 4);
+        assert_eq!(tetromino([(0, 0), (1, 0), (1, 1), (2, 1)]).orientation_count(), 4);
+        assert!(!tetromino([(0, 0), (1, 0), (2, 0), (0, 1)]).is_chiral());
+    }
+
+    #[test]
+    fn templates_should_count_orientations() {
+        let cell = Template::new(vec!(
+            Position::new(0, 0, 0),
+        ));
+        let block = Template::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(0, 1, 0),
+            Position::new(1, 0, 0),
+            Position::new(1, 1, 0),
+        ));
+
+        assert_eq!(cell.orientation_count(), 1);
+        assert_eq!(block.orientation_count(), 3);
+        assert_eq!(block.len(), 4);
+    }
+
+    #[test]
+    fn fixed_templates_should_have_a_single_orientation() {
+        let template = Template::fixed(vec!(
+            Position::new(2, 1, 0),
+            Position::new(3, 1, 0),
+            Position::new(3, 2, 0),
+            Position::new(3, 2, 1),
+        ));
+
+        let pieces: Vec<Piece<(i8, i8, i8)>> = (&template).into_iter().collect();
+
+        assert_eq!(pieces, vec!(Piece::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, 0, 0),
+            Position::new(1, 1, 0),
+            Position::new(1, 1, 1),
+        ))));
+        assert_eq!(template.proper_orientations().count(), 1);
+    }
+
+    #[test]
+    fn borrowed_templates_should_iterate_over_orientations() {
+        let template = Template::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(0, 1, 0),
+            Position::new(1, 0, 0),
+            Position::new(1, 1, 0),
+        ));
+
+        let mut count: usize = 0;
+        for piece in &template {
+            assert_eq!(piece.len(), 4);
+            count += 1;
+        }
+
+        assert_eq!(count, template.orientation_count());
+    }
+
+    #[test]
+    fn chiral_templates_should_have_twice_as_many_orientations_with_reflections() {
+        let template = Template::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, 0, 0),
+            Position::new(1, 1, 0),
+            Position::new(1, 1, 1),
+        )).with_reflections();
+
+        let proper = template.proper_orientations().count();
+
+        assert_eq!(proper, 12);
+        assert_eq!(template.orientation_count(), 2 * proper);
+    }
+
+    #[test]
+    fn templates_should_determine_chirality() {
+        let screw = Template::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, 0, 0),
+            Position::new(1, 1, 0),
+            Position::new(1, 1, 1),
+        ));
+        let flat_s = Template::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, 0, 0),
+            Position::new(1, 1, 0),
+            Position::new(2, 1, 0),
+        ));
+        let block = Template::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(0, 1, 0),
+            Position::new(1, 0, 0),
+            Position::new(1, 1, 0),
+        ));
+
+        assert!(screw.is_chiral());
+        assert!(screw.clone().with_reflections().is_chiral());
+        assert!(!flat_s.is_chiral());
+        assert!(!block.is_chiral());
+    }
+
+    #[test]
+    fn templates_with_reflections_should_return_mirror_images() {
+        let template = Template::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, 0, 0),
+            Position::new(1, 1, 0),
+            Position::new(1, 1, 1),
+            Position::new(1, 1, 2),
+        )).with_reflections();
+
+        let iterator: PieceIterator<(i8, i8, i8)> = template.into_iter();
+
+        assert_eq!(iterator.count(), 48);
+    }
+
+    #[test]
+    fn templates_should_color_all_orientations() {
+        let color = Rgb::new(0, 0, 255);
+        let template = Template::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, 0, 0),
+            Position::new(1, 1, 0),
+        )).with_color(color);
+
+        assert!(template.into_iter().all(|piece| piece.color() == Some(color)));
+    }
 }
@@ -20,6 +20,38 @@ impl Translation<(i8, i8, i8)> {
     pub fn new(x: i8, y: i8, z: i8) -> Translation<(i8, i8, i8)> {
         Translation { delta: (x, y, z) }
     }
+
+    /// The `Translation` that moves an entity by this `Translation` and then by
+    /// `other`. Returns `None` when it is beyond the range of the coordinates.
+    pub fn compose(&self, other: &Translation<(i8, i8, i8)>) -> Option<Translation<(i8, i8, i8)>> {
+        let (x, y, z) = self.delta;
+        let (dx, dy, dz) = other.delta;
+        Some(Translation::new(x.checked_add(dx)?, y.checked_add(dy)?, z.checked_add(dz)?))
+    }
+
+    /// The `Translation` that undoes this `Translation`. Returns `None` when it
+    /// is beyond the range of the coordinates.
+    pub fn negate(&self) -> Option<Translation<(i8, i8, i8)>> {
+        let (x, y, z) = self.delta;
+        Some(Translation::new(x.checked_neg()?, y.checked_neg()?, z.checked_neg()?))
+    }
+}
+
+impl Translation<(i8, i8)> {
+    /// The `Translation` that moves an entity by this `Translation` and then by
+    /// `other`. Returns `None` when it is beyond the range of the coordinates.
+    pub fn compose(&self, other: &Translation<(i8, i8)>) -> Option<Translation<(i8, i8)>> {
+        let (x, y) = self.delta;
+        let (dx, dy) = other.delta;
+        Some(Translation { delta: (x.checked_add(dx)?, y.checked_add(dy)?) })
+    }
+
+    /// The `Translation` that undoes this `Translation`. Returns `None` when it
+    /// is beyond the range of the coordinates.
+    pub fn negate(&self) -> Option<Translation<(i8, i8)>> {
+        let (x, y) = self.delta;
+        Some(Translation { delta: (x.checked_neg()?, y.checked_neg()?) })
+    }
 }
 
 /// Contract how to translate entities.
@@ -28,4 +60,31 @@ pub trait Translatable<T> where T: VectorAdd<T> {
     fn translate(&mut self, translation: &Translation<T>);
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compose_with_negation_should_be_zero() {
+        let translation = Translation::new(1, -2, 3);
+
+        assert_eq!(translation.compose(&translation.negate().unwrap()), Some(Translation::new(0, 0, 0)));
+        assert_eq!(translation.compose(&Translation::new(2, 2, 2)), Some(Translation::new(3, 0, 5)));
+
+        let planar = Translation::from((4, -1));
+        assert_eq!(planar.compose(&planar.negate().unwrap()), Some(Translation::from((0, 0))));
+    }
+
+    #[test]
+    fn translations_should_stop_at_the_range_of_the_coordinates() {
+        assert_eq!(Translation::new(i8::MIN, 0, 0).negate(), None);
+        assert_eq!(Translation::new(i8::MAX, 0, 0).negate(), Some(Translation::new(-i8::MAX, 0, 0)));
+        assert_eq!(Translation::new(0, i8::MAX, 0).compose(&Translation::new(0, 1, 0)), None);
+        assert_eq!(Translation::new(0, 0, i8::MIN).compose(&Translation::new(0, 0, -1)), None);
+
+        assert_eq!(Translation::from((0, i8::MIN)).negate(), None);
+        assert_eq!(Translation::from((i8::MAX, 0)).compose(&Translation::from((1, 0))), None);
+    }
+}
+
 
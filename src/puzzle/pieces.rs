@@ -1,19 +1,242 @@
 //! Containers that can dispense `Template`s.
 
-use super::piece::Template;
+use super::vector::VectorAdd;
+use super::piece::{Normalizable, Template, Transformable};
+
+/// How many copies of a `Template` in a `Bag` have to be used.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Bound {
+    /// Every copy has to be used.
+    Exactly(usize),
+    /// Any number of copies can be used, from none up to the count.
+    UpTo(usize),
+}
+
+impl Bound {
+    /// The number of copies that can still be used.
+    pub fn count(&self) -> usize {
+        match *self {
+            Bound::Exactly(count) | Bound::UpTo(count) => count,
+        }
+    }
+
+    /// Determine if the remaining copies may be left unused.
+    pub fn is_optional(&self) -> bool {
+        match *self {
+            Bound::Exactly(count) => count == 0,
+            Bound::UpTo(_) => true,
+        }
+    }
+
+    /// The same kind of `Bound`, with a different count.
+    fn with_count(&self, count: usize) -> Bound {
+        match *self {
+            Bound::Exactly(_) => Bound::Exactly(count),
+            Bound::UpTo(_) => Bound::UpTo(count),
+        }
+    }
+
+    fn same_kind(&self, other: &Bound) -> bool {
+        self.with_count(0) == other.with_count(0)
+    }
+
+    /// The same kind of `Bound`, with `count` more copies.
+    fn plus(&self, count: usize) -> Bound {
+        self.with_count(self.count() + count)
+    }
+}
 
 /// A container for `Template`s. Iterating over a `Bag` provides access to a
 /// tuple of a `Template` and the rest of the `Bag`.
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
 pub struct Bag<T> {
-    collection: Vec<(u8, Template<T>)>,
+    collection: Vec<(Bound, Template<T>)>,
 }
 
 impl<T> Bag<T> {
-    /// Create a `Bag` from a collection of Templates
-    pub fn new(collection: Vec<(u8, Template<T>)>) -> Self {
+    /// Create a `Bag` from a collection of Templates. The counts are upper
+    /// bounds, i.e. `Piece`s that are left over once the `Target` is packed
+    /// stay unused.
+    pub fn new(collection: Vec<(usize, Template<T>)>) -> Self {
+        Self::new_bounded(collection.into_iter().map(|(count, template)| (Bound::UpTo(count), template)).collect())
+    }
+
+    /// Create a `Bag` from a collection of Templates. Every copy of every
+    /// `Template` has to be used.
+    pub fn exactly(collection: Vec<(usize, Template<T>)>) -> Self {
+        Self::new_bounded(collection.into_iter().map(|(count, template)| (Bound::Exactly(count), template)).collect())
+    }
+
+    /// Create a `Bag` with a single copy of every `Template`.
+    pub fn from_templates(templates: Vec<Template<T>>) -> Self {
+        Self::new(templates.into_iter().map(|template| (1, template)).collect())
+    }
+
+    /// Create a `Bag` from a collection of Templates, with a `Bound` on the
+    /// number of copies of each `Template` that are used.
+    pub fn new_bounded(collection: Vec<(Bound, Template<T>)>) -> Self {
         Self { collection }
     }
+
+    /// The number of `Piece`s in the `Bag`, counting every copy.
+    pub fn len(&self) -> usize {
+        self.collection.iter().map(|(bound, _)| bound.count()).sum()
+    }
+
+    /// Determine if there are no `Piece`s left in the `Bag`.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Determine if the `Piece`s left in the `Bag` may stay unused, i.e. if
+    /// they are all optional.
+    pub fn is_satisfied(&self) -> bool {
+        self.collection.iter().all(|(bound, _)| bound.is_optional())
+    }
+
+    /// Iterate over the distinct `Template`s in the `Bag` with their counts,
+    /// without consuming the `Bag`.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &Template<T>)> {
+        self.collection.iter().map(|(bound, template)| (bound.count(), template))
+    }
+
+    /// Add up to `count` copies of a `Template` to the `Bag`, like `Bag::new`
+    /// does. When the `Bag` already contains the `Template`, its count is
+    /// increased. Adding no copies leaves the `Bag` as it is.
+    pub fn add(&mut self, count: usize, template: Template<T>) where T: PartialEq {
+        if count == 0 {
+            return;
+        }
+        let bound = Bound::UpTo(count);
+        match self.collection.iter_mut().find(|(candidate_bound, candidate)| candidate_bound.same_kind(&bound) && *candidate == template) {
+            Some(entry) => entry.0 = entry.0.plus(count),
+            None => self.collection.push((bound, template)),
+        }
+    }
+
+    /// Remove a single copy of a `Template` from the `Bag`. Returns `false` when
+    /// the `Bag` does not contain the `Template`.
+    pub fn remove(&mut self, template: &Template<T>) -> bool where T: PartialEq {
+        match self.collection.iter().position(|(bound, candidate)| bound.count() > 0 && candidate == template) {
+            Some(index) => {
+                take(&mut self.collection, index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The number of cells of all the `Template`s in the `Bag` together, taking
+    /// their counts into account.
+    pub fn total_volume(&self) -> usize {
+        self.collection.iter().map(|(bound, template)| bound.count() * template.len()).sum()
+    }
+
+    /// The number of cells of the `Template`s in the `Bag` that have to be
+    /// used, i.e. the smallest volume the `Bag` can pack.
+    pub fn required_volume(&self) -> usize {
+        self.collection
+            .iter()
+            .filter(|(bound, _)| !bound.is_optional())
+            .map(|(bound, template)| bound.count() * template.len())
+            .sum()
+    }
+
+    /// Combine equal `Template`s into a single entry, adding up their counts.
+    /// The solver then uses the copies in a fixed order, instead of trying
+    /// every permutation of them.
+    pub(crate) fn merge_identical(self) -> Bag<T> where T: PartialEq {
+        Bag::new_bounded(merge(self.collection, |candidate, template| candidate == template))
+    }
+
+    /// Combine `Template`s that are the same shape, up to rotation and
+    /// translation, into a single entry, adding up their counts. The first of
+    /// them is kept.
+    pub fn deduplicate(&mut self) where T: Clone + PartialOrd + Ord + Transformable + Normalizable<T> + VectorAdd<T> {
+        let collection: Vec<(Bound, Template<T>)> = self.collection.drain(..).collect();
+        self.collection = merge(collection, |candidate, template| candidate.same_shape(template));
+    }
+
+    /// The entries of the `Bag`, i.e. every distinct `Template` with its `Bound`.
+    pub(crate) fn into_entries(self) -> impl Iterator<Item = (Bound, Template<T>)> {
+        self.collection.into_iter()
+    }
+
+    /// The distinct `Template`s that are still in the `Bag`.
+    pub(crate) fn templates(&self) -> impl Iterator<Item = &Template<T>> {
+        self.collection.iter().map(|(_, template)| template)
+    }
+
+    /// Take the first `Template` out of the `Bag`. Returns that `Template` and the
+    /// rest of the `Bag`, with the order of the remaining `Template`s preserved.
+    pub fn split_first(&self) -> Option<(Template<T>, Bag<T>)> where T: Clone {
+        let (_, template) = self.collection.first()?;
+        let mut collection: Vec<(Bound, Template<T>)> = self.collection.to_vec();
+        take(&mut collection, 0);
+        Some((template.clone(), Bag::new_bounded(collection)))
+    }
+}
+
+impl<T> Bag<T> where T: Clone {
+    /// Take a copy of the first `Template` out of the `Bag` that has copies
+    /// left, whatever its `Bound`. Returns that `Template` and the rest of the
+    /// `Bag`.
+    pub(crate) fn split_first_counted(&self) -> Option<(Template<T>, Bag<T>)> {
+        let index = self.first_counted()?;
+        let mut collection: Vec<(Bound, Template<T>)> = self.collection.to_vec();
+        take(&mut collection, index);
+        Some((self.collection[index].1.clone(), Bag::new_bounded(collection)))
+    }
+
+    /// The `Bag` without any copies of the `Template` that `split_first_counted`
+    /// takes, when those copies may stay unused.
+    pub(crate) fn without_first_counted(&self) -> Option<Bag<T>> {
+        let index = self.first_counted().filter(|&index| self.collection[index].0.is_optional())?;
+        let mut collection: Vec<(Bound, Template<T>)> = self.collection.to_vec();
+        collection.remove(index);
+        Some(Bag::new_bounded(collection))
+    }
+
+    fn first_counted(&self) -> Option<usize> {
+        self.collection.iter().position(|(bound, _)| bound.count() > 0)
+    }
+}
+
+impl<T> Bag<T> {
+    /// The same `Bag`, with the entries sorted by `key` of their `Template`s.
+    /// Entries with equal keys keep their order.
+    pub(crate) fn sorted_by_key<K, F>(mut self, mut key: F) -> Bag<T> where K: Ord, F: FnMut(&Template<T>) -> K {
+        self.collection.sort_by_cached_key(|(_, template)| key(template));
+        self
+    }
+}
+
+/// Add up the counts of the entries with the same kind of `Bound` whose
+/// `Template`s are the `same`, keeping the first `Template`. Entries without
+/// copies are dropped.
+fn merge<T, S>(collection: Vec<(Bound, Template<T>)>, same: S) -> Vec<(Bound, Template<T>)> where S: Fn(&Template<T>, &Template<T>) -> bool {
+    let mut merged: Vec<(Bound, Template<T>)> = vec!();
+    for (bound, template) in collection {
+        if bound.count() == 0 {
+            continue;
+        }
+        match merged.iter_mut().find(|(candidate_bound, candidate)| candidate_bound.same_kind(&bound) && same(candidate, &template)) {
+            Some(entry) => entry.0 = entry.0.plus(bound.count()),
+            None => merged.push((bound, template)),
+        }
+    }
+    merged
+}
+
+/// Use a single copy of the entry at `index`, removing the entry once its last
+/// copy is used. The order of the other entries is preserved.
+fn take<T>(collection: &mut Vec<(Bound, Template<T>)>, index: usize) {
+    let bound = collection[index].0;
+    if bound.count() > 1 {
+        collection[index].0 = bound.with_count(bound.count() - 1);
+    } else {
+        collection.remove(index);
+    }
 }
 
 impl<T> IntoIterator for Bag<T> where T: Clone {
@@ -28,7 +251,7 @@ impl<T> IntoIterator for Bag<T> where T: Clone {
 
 /// Iterator over tuples of `Template`s and rest of `Bag`s.
 pub struct BagSelectionIterator<T> {
-    collection: Vec<(u8, Template<T>)>,
+    collection: Vec<(Bound, Template<T>)>,
     index: usize,
 }
 
@@ -43,17 +266,18 @@ impl<T> Iterator for BagSelectionIterator<T> where T: Clone {
 
     fn next(&mut self) -> Option<(Template<T>, Bag<T>)> {
         if self.index < self.collection.len() {
-            let mut collection: Vec<(u8, Template<T>)> = self.collection.to_vec();
+            let mut collection: Vec<(Bound, Template<T>)> = self.collection.to_vec();
             let template;
-            if collection[self.index].0 > 1 {
+            let bound = collection[self.index].0;
+            if bound.count() > 1 {
                 template = collection[self.index].1.clone();
-                collection[self.index].0 -= 1;
+                collection[self.index].0 = bound.with_count(bound.count() - 1);
             } else {
                 let pair = collection.swap_remove(self.index);
                 template = pair.1.clone();
             }
             self.index += 1;
-            Some((template, Bag::new(collection)))
+            Some((template, Bag::new_bounded(collection)))
         } else {
             None
         }
@@ -89,4 +313,152 @@ mod tests {
                 Position::new(1, 0, 0)))))));
 
     }
+
+    #[test]
+    fn bag_should_determine_total_volume() {
+        let bag = Bag::new(vec!(
+            (3, Template::new(vec!(Position::new(0, 0, 0)))),
+            (2, Template::new(vec!(Position::new(0, 0, 0), Position::new(1, 0, 0)))),
+        ));
+
+        assert_eq!(bag.total_volume(), 7);
+    }
+
+    #[test]
+    fn from_templates_should_use_every_template_once() {
+        let monomino = Template::new(vec!(Position::new(0, 0, 0)));
+        let domino = Template::new(vec!(Position::new(0, 0, 0), Position::new(1, 0, 0)));
+
+        let bag = Bag::from_templates(vec!(monomino.clone(), domino.clone()));
+
+        assert_eq!(bag.total_volume(), monomino.len() + domino.len());
+        assert_eq!(bag, Bag::new(vec!((1, monomino), (1, domino))));
+    }
+
+    #[test]
+    fn bag_should_count_remaining_pieces() {
+        let monomino = Template::new(vec!(Position::new(0, 0, 0)));
+        let domino = Template::new(vec!(Position::new(0, 0, 0), Position::new(1, 0, 0)));
+        let bag = Bag::new(vec!((2, monomino.clone()), (1, domino.clone())));
+
+        assert_eq!(bag.len(), 3);
+        assert!(!bag.is_empty());
+        assert_eq!(bag.iter().collect::<Vec<_>>(), vec!((2, &monomino), (1, &domino)));
+
+        let (_, rest) = bag.into_iter().next().unwrap();
+        assert_eq!(rest.len(), 2);
+        assert_eq!(rest.iter().collect::<Vec<_>>(), vec!((1, &monomino), (1, &domino)));
+        assert!(Bag::<(i8, i8, i8)>::new(vec!()).is_empty());
+    }
+
+    #[test]
+    fn add_and_remove_should_update_counts() {
+        let monomino = Template::new(vec!(Position::new(0, 0, 0)));
+        let domino = Template::new(vec!(Position::new(0, 0, 0), Position::new(1, 0, 0)));
+        let mut bag = Bag::new(vec!());
+
+        bag.add(2, monomino.clone());
+        bag.add(1, domino.clone());
+        bag.add(1, monomino.clone());
+        bag.add(0, Template::new(vec!(Position::new(0, 0, 0), Position::new(0, 1, 0))));
+        assert_eq!(bag.total_volume(), 5);
+        assert_eq!(bag, Bag::new(vec!((3, monomino.clone()), (1, domino.clone()))));
+
+        assert!(bag.remove(&domino));
+        assert!(!bag.remove(&domino));
+        assert!(bag.remove(&monomino));
+        assert_eq!(bag.total_volume(), 2);
+        assert_eq!(bag, Bag::new(vec!((2, monomino))));
+    }
+
+    #[test]
+    fn merge_identical_should_add_counts_of_equal_templates() {
+        let bag = Bag::new(vec!(
+            (1, Template::new(vec!(Position::new(0, 0, 0)))),
+            (2, Template::new(vec!(Position::new(0, 0, 0), Position::new(1, 0, 0)))),
+            (2, Template::new(vec!(Position::new(0, 0, 0)))),
+        ));
+
+        assert_eq!(bag.merge_identical(), Bag::new(vec!(
+            (3, Template::new(vec!(Position::new(0, 0, 0)))),
+            (2, Template::new(vec!(Position::new(0, 0, 0), Position::new(1, 0, 0)))),
+        )));
+    }
+
+    #[test]
+    fn deduplicate_should_merge_rotated_templates() {
+        let ell = Template::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, 0, 0),
+            Position::new(2, 0, 0),
+            Position::new(2, 1, 0),
+        ));
+        let rotated = Template::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(0, 1, 0),
+            Position::new(0, 2, 0),
+            Position::new(-1, 2, 0),
+        ));
+        let mut bag = Bag::new(vec!((1, ell.clone()), (1, rotated)));
+
+        bag.deduplicate();
+
+        assert_eq!(bag, Bag::new(vec!((2, ell))));
+    }
+
+    #[test]
+    fn counts_should_exceed_a_byte() {
+        let monomino = Template::new(vec!(Position::new(0, 0, 0)));
+        let mut bag = Bag::new(vec!((255, monomino.clone())));
+
+        bag.add(1, monomino.clone());
+        assert_eq!(bag, Bag::new(vec!((256, monomino.clone()))));
+
+        let mut bag = Bag::new(vec!((200, monomino.clone()), (100, monomino.clone()), (0, monomino.clone())));
+        bag.deduplicate();
+        assert_eq!(bag.len(), 300);
+        assert_eq!(bag, Bag::new(vec!((300, monomino.clone()))));
+        assert_eq!(Bag::new(vec!((0, monomino))).merge_identical(), Bag::new(vec!()));
+    }
+
+    #[test]
+    fn bounds_should_determine_if_bag_is_satisfied() {
+        let monomino = Template::new(vec!(Position::new(0, 0, 0)));
+        let domino = Template::new(vec!(Position::new(0, 0, 0), Position::new(1, 0, 0)));
+        let bag = Bag::new_bounded(vec!((Bound::Exactly(1), domino.clone()), (Bound::UpTo(2), monomino.clone())));
+
+        assert_eq!(bag.len(), 3);
+        assert!(!bag.is_satisfied());
+        let (template, rest) = bag.split_first().unwrap();
+        assert_eq!(template, domino);
+        assert!(rest.is_satisfied());
+        assert_eq!(rest, Bag::new_bounded(vec!((Bound::UpTo(2), monomino))));
+        assert!(!Bag::exactly(vec!((1, domino.clone()))).is_satisfied());
+        assert!(Bag::new(vec!((1, domino))).is_satisfied());
+        assert!(Bag::<(i8, i8, i8)>::new(vec!()).is_satisfied());
+    }
+
+    #[test]
+    fn split_first_should_preserve_order_of_rest() {
+        let bag = Bag::new(vec!(
+            (2, Template::new(vec!(Position::new(0, 0, 0)))),
+            (1, Template::new(vec!(Position::new(0, 0, 0), Position::new(1, 0, 0)))),
+            (1, Template::new(vec!(Position::new(0, 0, 0), Position::new(0, 1, 0)))),
+        ));
+
+        let (template, rest) = bag.split_first().unwrap();
+        assert_eq!(template, Template::new(vec!(Position::new(0, 0, 0))));
+        assert_eq!(rest, Bag::new(vec!(
+            (1, Template::new(vec!(Position::new(0, 0, 0)))),
+            (1, Template::new(vec!(Position::new(0, 0, 0), Position::new(1, 0, 0)))),
+            (1, Template::new(vec!(Position::new(0, 0, 0), Position::new(0, 1, 0)))),
+        )));
+
+        let (_, rest) = rest.split_first().unwrap();
+        let (template, rest) = rest.split_first().unwrap();
+        assert_eq!(template, Template::new(vec!(Position::new(0, 0, 0), Position::new(1, 0, 0))));
+        assert_eq!(rest, Bag::new(vec!(
+            (1, Template::new(vec!(Position::new(0, 0, 0), Position::new(0, 1, 0)))),
+        )));
+    }
 }
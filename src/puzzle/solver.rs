@@ -1,30 +1,99 @@
 //! Solver for packing problems.
 use std::fmt::{Display, Formatter, Error};
+use std::cmp;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::hash::Hash;
+use std::io::{self, BufRead, Write};
+use std::iter::FromIterator;
+use std::ops::ControlFlow;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
 
-use super::vector::{VectorAdd, VectorDifference};
-use super::piece::{MinimumPosition, Position, Positionable, Translatable, Transformable, Normalizable, Piece};
+use super::vector::{VectorAdd, VectorDifference, VectorExtremes};
+use super::piece::{bounding_box, color_balance, CubeSymmetry, CubeSymmetryIterator, MinimumPosition, Position, Positionable, Translatable, Translation, Transformable, Normalizable, Piece, Template};
 use super::pieces::Bag;
 
 /// Region to be packed.
-#[derive(Debug, PartialEq, Eq, Hash)]
+///
+/// The cells of a `Target` are kept in sorted order, so `Target`s are compared
+/// and hashed as sets of `Position`s: two `Target`s with the same cells are
+/// equal and hash identically, regardless of the order in which the cells were
+/// given. This makes a `Target` suitable as the key of a cache of subproblems.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "TargetFields<T>", bound(deserialize = "T: serde::Deserialize<'de> + Ord + Clone")))]
 pub struct Target<T> {
     collection: Vec<Position<T>>,
 }
 
+/// The fields of a `Target` as they are read, before it is created with
+/// `Target::new`.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct TargetFields<T> {
+    collection: Vec<Position<T>>,
+}
+
+#[cfg(feature = "serde")]
+impl<T> From<TargetFields<T>> for Target<T> where T: Ord + Clone {
+    fn from(fields: TargetFields<T>) -> Self {
+        Target::new(fields.collection)
+    }
+}
+
 impl<T> Target<T> where T: PartialOrd + Ord + PartialEq + Eq + Clone {
     /// Create a new `Target` from a collection of `Position`s.
-    pub fn new(collection: Vec<Position<T>>) -> Target<T> {
+    pub fn new(mut collection: Vec<Position<T>>) -> Target<T> {
+        collection.sort();
+        Target { collection }
+    }
+
+    /// Create the `Target` that is covered by the `Piece`s together, i.e. the
+    /// union of their `Position`s.
+    pub fn from_pieces(pieces: &[Piece<T>]) -> Target<T> {
+        let mut collection: Vec<Position<T>> = pieces.iter().flat_map(|piece| piece.iter()).collect();
+        collection.sort();
+        collection.dedup();
         Target { collection }
     }
 
+    /// The number of cells in the `Target`.
+    pub fn volume(&self) -> usize {
+        self.collection.len()
+    }
+
+    /// The minimum and maximum corner of the smallest box that contains this
+    /// `Target`, or `None` for a `Target` without cells.
+    pub fn bounding_box(&self) -> Option<(Position<T>, Position<T>)> where T: VectorExtremes<T> {
+        bounding_box(&self.collection)
+    }
+
+    /// The `Position`s that still need to be packed, in sorted order.
+    pub(crate) fn positions(&self) -> &[Position<T>] {
+        &self.collection
+    }
+
     /// Determine if there is nothing left to pack.
     pub fn is_packed(&self) -> bool {
         self.collection.is_empty()
     }
 
-    /// Determine if a `Piece` can be placed in the `Target`.
+    /// Determine if a `Position` is a cell of the `Target`.
+    pub fn contains(&self, position: &Position<T>) -> bool {
+        self.collection.binary_search(position).is_ok()
+    }
+
+    /// Determine if a `Piece` can be placed in the `Target`, i.e. if every
+    /// `Position` of the `Piece` is a cell of the `Target`.
     pub fn fits(&self, piece: &Piece<T>) -> bool {
-        piece.iter().all(|position| self.collection.contains(&position))
+        piece.iter().all(|position| self.contains(&position))
+    }
+
+    /// Determine if every `Position` of the `Piece` is a cell of the `Target`.
+    /// This is the same check as `fits`.
+    pub fn contains_piece(&self, piece: &Piece<T>) -> bool {
+        self.fits(piece)
     }
 
     /// Place a `Piece` in the `Target`. *Note* caller is responsible to check
@@ -40,22 +109,387 @@ impl<T> Target<T> where T: PartialOrd + Ord + PartialEq + Eq + Clone {
     }
 }
 
+impl<T> Target<T> where T: Clone + PartialOrd + Ord + Transformable + Normalizable<T> + VectorDifference<T> + VectorAdd<T> {
+    /// All the placements of a `Template` that fit in the `Target` and cover
+    /// `position`. Every orientation of the `Template` is tried, and each
+    /// distinct placement is reported once.
+    pub fn placements_covering(&self, position: &Position<T>, template: &Template<T>) -> Vec<Piece<T>> {
+        let mut placements: Vec<Piece<T>> = vec!();
+        let mut seen: BTreeSet<Vec<Position<T>>> = BTreeSet::new();
+        for piece in template.clone() {
+            for cell in piece.iter() {
+                let mut candidate = piece.clone();
+                candidate.translate(&cell.to(position));
+                if self.fits(&candidate) && seen.insert(candidate.iter().collect()) {
+                    placements.push(candidate);
+                }
+            }
+        }
+        placements
+    }
+
+    /// All the placements of a `Template` that fit in the `Target`. Every
+    /// orientation of the `Template` is moved so its minimum `Position` lands on
+    /// each cell of the `Target`, and each distinct placement is reported once.
+    pub fn placements(&self, template: &Template<T>) -> Vec<Piece<T>> {
+        let mut placements: Vec<Piece<T>> = vec!();
+        let mut seen: BTreeSet<Vec<Position<T>>> = BTreeSet::new();
+        for piece in template.clone() {
+            let block = match piece.minimum_position() {
+                Some(block) => block,
+                None => continue,
+            };
+            for cell in &self.collection {
+                let candidate = piece.translated(&block.to(cell));
+                if self.fits(&candidate) && seen.insert(candidate.iter().collect()) {
+                    placements.push(candidate);
+                }
+            }
+        }
+        placements
+    }
+
+    fn is_coverable(&self, position: &Position<T>, bag: &Bag<T>) -> bool {
+        bag.templates().any(|template| !self.placements_covering(position, template).is_empty())
+    }
+}
+
+impl Target<(i8, i8)> {
+    /// Create a rectangle `Target` with a corner at the origin, containing every
+    /// `Position::d2(x, y)` with `0 <= x < width` and `0 <= y < height`. Zero or
+    /// negative dimensions give an empty `Target`.
+    pub fn rectangle(width: i8, height: i8) -> Target<(i8, i8)> {
+        let mut positions: Vec<Position<(i8, i8)>> = vec!();
+        for x in 0..width {
+            for y in 0..height {
+                positions.push(Position::d2(x, y));
+            }
+        }
+        Target::new(positions)
+    }
+}
+
+/// Reasons why a box shaped `Target` can not be created.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum DimensionError {
+    /// The dimension is so large that coordinates would not fit in an `i8`.
+    TooLarge(usize),
+    /// The rows or layers of a grid do not all have the same length.
+    Inconsistent,
+}
+
+impl Display for DimensionError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        match *self {
+            DimensionError::TooLarge(dimension) => write!(f, "dimension {} exceeds the coordinate range", dimension),
+            DimensionError::Inconsistent => write!(f, "grid dimensions are inconsistent"),
+        }
+    }
+}
+
+impl std::error::Error for DimensionError {}
+
+/// Two `Piece`s of a `Solution` cover the same cell.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Overlap {
+    /// Index of the `Piece` that was placed first.
+    pub first: usize,
+    /// Index of the `Piece` that was placed second.
+    pub second: usize,
+}
+
+impl Display for Overlap {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "pieces {} and {} overlap", self.first, self.second)
+    }
+}
+
+impl std::error::Error for Overlap {}
+
+/// Reasons why the solver can not search for packings.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SolverError {
+    /// The `Target` has no cells.
+    EmptyTarget,
+    /// A `Template` in the `Bag` has no cells.
+    EmptyPiece,
+}
+
+impl Display for SolverError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        match *self {
+            SolverError::EmptyTarget => write!(f, "the target has no cells"),
+            SolverError::EmptyPiece => write!(f, "a piece has no cells"),
+        }
+    }
+}
+
+impl std::error::Error for SolverError {}
+
+/// Reasons why a `Bag` can not pack a `Target`, found without searching.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ValidationError {
+    /// The `Target` has no cells.
+    EmptyTarget,
+    /// A `Template` in the `Bag` has no cells.
+    EmptyPiece,
+    /// The `Piece`s that can be used can not cover exactly the cells of the
+    /// `Target`.
+    VolumeMismatch {
+        /// Number of cells of the `Target`.
+        target: usize,
+        /// Number of cells of the `Piece`s that have to be used.
+        required: usize,
+        /// Number of cells of all the `Piece`s together.
+        available: usize,
+    },
+    /// The `Piece`s can not be placed so that they cover as many cells of
+    /// each color of a 3D checkerboard as the `Target` has.
+    ColorImbalance,
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        match *self {
+            ValidationError::EmptyTarget => write!(f, "the target has no cells"),
+            ValidationError::EmptyPiece => write!(f, "a piece has no cells"),
+            ValidationError::VolumeMismatch { target, required, available } => write!(f, "pieces cover between {} and {} cells but the target has {}", required, available, target),
+            ValidationError::ColorImbalance => write!(f, "the pieces can not match the checkerboard coloring of the target"),
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+impl Target<(i8, i8, i8)> {
+    /// Create a cuboid `Target` with a corner at the origin, containing every
+    /// `Position::new(x, y, z)` with `0 <= x < width`, `0 <= y < height` and
+    /// `0 <= z < depth`. Zero or negative dimensions give an empty `Target`.
+    pub fn cuboid(width: i8, height: i8, depth: i8) -> Target<(i8, i8, i8)> {
+        let extent = |dimension: i8| dimension.max(0) as usize;
+        Target::try_from_box(extent(width), extent(height), extent(depth))
+            .expect("dimensions of an i8 to fit")
+    }
+
+    /// Create the cuboid `Target` of `dimensions`, `(width, height, depth)`,
+    /// with the `holes` removed. A hole outside the box is ignored.
+    pub fn box_minus(dimensions: (i8, i8, i8), holes: &[Position<(i8, i8, i8)>]) -> Target<(i8, i8, i8)> {
+        let (width, height, depth) = dimensions;
+        Target::cuboid(width, height, depth)
+            .collection
+            .into_iter()
+            .filter(|position| !holes.contains(position))
+            .collect()
+    }
+
+    /// The `(width, height, depth)` of the bounding box of this `Target`, or
+    /// `(0, 0, 0)` for a `Target` without cells.
+    pub fn dimensions(&self) -> (usize, usize, usize) {
+        match self.bounding_box() {
+            Some((minimum, maximum)) => {
+                let (min_x, min_y, min_z) = *minimum.coordinates();
+                let (max_x, max_y, max_z) = *maximum.coordinates();
+                let size = |min: i8, max: i8| (max as isize - min as isize + 1) as usize;
+                (size(min_x, max_x), size(min_y, max_y), size(min_z, max_z))
+            }
+            None => (0, 0, 0),
+        }
+    }
+
+    /// Create a box shaped `Target` with the given dimensions, with one corner
+    /// at the origin. Fails when the coordinates of the box would not fit in an
+    /// `i8`, instead of silently wrapping around.
+    pub fn try_from_box(width: usize, height: usize, depth: usize) -> Result<Target<(i8, i8, i8)>, DimensionError> {
+        let limit = i8::MAX as usize + 1;
+        for dimension in &[width, height, depth] {
+            if *dimension > limit {
+                return Err(DimensionError::TooLarge(*dimension));
+            }
+        }
+        let mut positions: Vec<Position<(i8, i8, i8)>> = Vec::with_capacity(width * height * depth);
+        for x in 0..width {
+            for y in 0..height {
+                for z in 0..depth {
+                    positions.push(Position::new(x as i8, y as i8, z as i8));
+                }
+            }
+        }
+        Ok(Target::new(positions))
+    }
+
+    /// The number of cells with an even coordinate sum minus the number of cells
+    /// with an odd coordinate sum, i.e. the balance of the `Target` on a 3D
+    /// checkerboard.
+    pub fn color_balance(&self) -> i32 {
+        color_balance(&self.collection)
+    }
+
+    /// The maximal connected regions of the `Target`, where cells are connected
+    /// when they share a face.
+    pub fn connected_components(&self) -> Vec<Target<(i8, i8, i8)>> {
+        let mut unvisited: HashSet<Position<(i8, i8, i8)>> = self.collection.iter().cloned().collect();
+        let mut components: Vec<Target<(i8, i8, i8)>> = vec!();
+        for start in &self.collection {
+            if !unvisited.remove(start) {
+                continue;
+            }
+            let mut component: Vec<Position<(i8, i8, i8)>> = vec!(start.clone());
+            let mut frontier: Vec<Position<(i8, i8, i8)>> = vec!(start.clone());
+            while let Some(position) = frontier.pop() {
+                for neighbour in position.neighbors() {
+                    if unvisited.remove(&neighbour) {
+                        component.push(neighbour.clone());
+                        frontier.push(neighbour);
+                    }
+                }
+            }
+            components.push(Target::new(component));
+        }
+        components
+    }
+
+    /// Draw the `Target` one z-layer at a time, each under a `z = ...` header.
+    /// A layer is a grid of the bounding box of the `Target`, one row per line,
+    /// with `#` for cells of the `Target` and `.` for the others.
+    pub fn to_layers(&self) -> String {
+        let (minimum, maximum) = match bounding_box(&self.collection) {
+            Some(corners) => corners,
+            None => return String::new(),
+        };
+        let (min_x, min_y, min_z) = *minimum.coordinates();
+        let (max_x, max_y, max_z) = *maximum.coordinates();
+        let cells: HashSet<&Position<(i8, i8, i8)>> = self.collection.iter().collect();
+
+        let mut layers = String::new();
+        for z in min_z..=max_z {
+            layers.push_str(&format!("z = {}\n", z));
+            for y in min_y..=max_y {
+                for x in min_x..=max_x {
+                    layers.push(if cells.contains(&Position::new(x, y, z)) { '#' } else { '.' });
+                }
+                layers.push('\n');
+            }
+        }
+        layers
+    }
+
+    /// Determine if every cell of the `Target` can be reached from every other
+    /// cell by stepping between cells that share a face. An empty `Target` is
+    /// connected.
+    pub fn is_connected(&self) -> bool {
+        self.connected_components().len() <= 1
+    }
+
+    /// Create a `Target` from an occupancy grid, where `grid[z][y][x]` tells if
+    /// `Position::new(x, y, z)` is part of the `Target`. All layers should have
+    /// the same number of rows and all rows the same length.
+    pub fn from_grid(grid: &[Vec<Vec<bool>>]) -> Result<Target<(i8, i8, i8)>, DimensionError> {
+        let depth = grid.len();
+        let height = grid.first().map_or(0, |layer| layer.len());
+        let width = grid.first().and_then(|layer| layer.first()).map_or(0, |row| row.len());
+        if grid.iter().any(|layer| layer.len() != height || layer.iter().any(|row| row.len() != width)) {
+            return Err(DimensionError::Inconsistent);
+        }
+        let limit = i8::MAX as usize + 1;
+        for dimension in &[width, height, depth] {
+            if *dimension > limit {
+                return Err(DimensionError::TooLarge(*dimension));
+            }
+        }
+
+        let mut positions: Vec<Position<(i8, i8, i8)>> = vec!();
+        for (z, layer) in grid.iter().enumerate() {
+            for (y, row) in layer.iter().enumerate() {
+                for (x, occupied) in row.iter().enumerate() {
+                    if *occupied {
+                        positions.push(Position::new(x as i8, y as i8, z as i8));
+                    }
+                }
+            }
+        }
+        Ok(Target::new(positions))
+    }
+
+    /// Read a `Target` from comma separated values. Every non-blank line holds
+    /// the coordinates of a single `Position`, i.e. `x,y,z`.
+    pub fn from_csv<R>(reader: R) -> io::Result<Target<(i8, i8, i8)>> where R: BufRead {
+        let mut positions: Vec<Position<(i8, i8, i8)>> = vec!();
+        for line in reader.lines() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let coordinates = line
+                .split(',')
+                .map(|coordinate| coordinate.trim().parse::<i8>())
+                .collect::<Result<Vec<i8>, _>>()
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+            if coordinates.len() != 3 {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, format!("expected x,y,z but found \"{}\"", line)));
+            }
+            positions.push(Position::new(coordinates[0], coordinates[1], coordinates[2]));
+        }
+        Ok(Target::new(positions))
+    }
+
+    /// Write a `Target` as comma separated values, one `Position` per line.
+    /// Positions are written in sorted order.
+    pub fn to_csv<W>(&self, writer: &mut W) -> io::Result<()> where W: Write {
+        for position in &self.collection {
+            let (x, y, z) = position.coordinates();
+            writeln!(writer, "{},{},{}", x, y, z)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T> FromIterator<Position<T>> for Target<T> where T: Ord {
+    fn from_iter<I>(positions: I) -> Self where I: IntoIterator<Item = Position<T>> {
+        let mut collection: Vec<Position<T>> = positions.into_iter().collect();
+        collection.sort();
+        Target { collection }
+    }
+}
+
+/// Rotates or reflects all the cells of the `Target` together.
+impl<T> Transformable for Target<T> where T: Transformable + PartialOrd + Ord {
+    fn transform(&mut self, symmetry: &CubeSymmetry) {
+        for position in &mut self.collection {
+            position.transform(symmetry);
+        }
+        self.collection.sort()
+    }
+}
+
+/// Moves all the cells of the `Target` together, which keeps them in order.
+impl<T> Translatable<T> for Target<T> where T: VectorAdd<T> {
+    fn translate(&mut self, translation: &Translation<T>) {
+        for position in &mut self.collection {
+            position.translate(translation);
+        }
+    }
+}
+
 impl<T> MinimumPosition<T> for Target<T> where T: PartialOrd + Ord + Clone {
     fn minimum_position(&self) -> Option<Position<T>> {
-        self.collection.iter().min().cloned()
+        self.collection.first().cloned()
     }
 }
 
 /// (Partial) solution of a packing problem. Piece at their correct location are listed.
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Solution<T> {
-    pieces: Vec<Piece<T>>
+    pieces: Vec<Piece<T>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    symmetries: Vec<Option<CubeSymmetry>>,
 }
 
 impl<T> Solution<T> where T : Clone {
     /// Empty solution. Serves as a starting point for the `solve` method.
     pub fn empty() -> Solution<T> {
-        Solution { pieces: vec!() }
+        Solution { pieces: vec!(), symmetries: vec!() }
     }
 
     /// Record a `Piece` as part of the `Solution`.
@@ -63,14 +497,146 @@ impl<T> Solution<T> where T : Clone {
     /// Returns a new `Solutions` with the `Piece` added. *Note* the caller is
     /// responsible for checking if the `Piece` actually fits in the `Target`.
     pub fn record(&self, piece: &Piece<T>) -> Solution<T> {
+        self.record_with_symmetry(piece, None)
+    }
+
+    /// Record a `Piece` as part of the `Solution`, together with the
+    /// `CubeSymmetry` that orients its `Template` like the `Piece`, if known.
+    pub fn record_with_symmetry(&self, piece: &Piece<T>, symmetry: Option<CubeSymmetry>) -> Solution<T> {
         let mut pieces: Vec<Piece<T>> = self.pieces.to_vec();
         pieces.push(piece.clone());
+        let mut symmetries: Vec<Option<CubeSymmetry>> = self.symmetries.to_vec();
+        symmetries.push(symmetry);
+
+        Solution { pieces, symmetries }
+    }
+
+    /// For every `Piece`, in the order they were recorded, the `CubeSymmetry`
+    /// that orients its `Template` like the `Piece`. Applying it to the
+    /// `Template` gives the shape of the `Piece`, up to translation. It is
+    /// recorded by the searches that place orientations of the `Template`s:
+    /// `solve` and the variants built on it, `solutions`, `solve_ordered`,
+    /// `solve_hardest_first`, `solve_parallel`, `solve_mutating`,
+    /// `solve_memoized` and the bitmask `solve`. It is `None` for the `Piece`s
+    /// that are picked from the placements in a `Target`, which is how the
+    /// `MostConstrained` strategy, `solve_fixing_first` and the exact cover
+    /// search of `solve_exact_cover` choose them.
+    pub fn symmetries(&self) -> &[Option<CubeSymmetry>] {
+        &self.symmetries
+    }
+
+    /// The `Target` that this `Solution` covers. For a correct `Solution` this is
+    /// the `Target` that was packed, with its `Position`s in sorted order.
+    pub fn covered_target(&self) -> Target<T> where T: Ord {
+        Target::from_pieces(&self.pieces)
+    }
+
+    /// Determine if a cell is covered by more than one `Piece`. This is a cheap
+    /// invariant check, since neither `Target::place` nor `Solution::record`
+    /// verify that `Piece`s do not overlap.
+    pub fn has_overlap(&self) -> bool where T: Ord {
+        let mut seen: BTreeSet<Position<T>> = BTreeSet::new();
+        self.pieces.iter().flat_map(|piece| piece.iter()).any(|position| !seen.insert(position))
+    }
+
+    /// The part of `target` that is still open, i.e. `target` with every
+    /// `Piece` of this `Solution` placed in it.
+    pub fn remaining(&self, target: &Target<T>) -> Target<T> where T: Ord {
+        self.pieces.iter().fold(target.clone(), |remaining, piece| remaining.place(piece))
+    }
+
+    /// The `Piece`s of the `Solution`, in the order they were recorded.
+    pub fn pieces(&self) -> &[Piece<T>] {
+        &self.pieces
+    }
+
+    /// The number of `Piece`s in the `Solution`.
+    pub fn len(&self) -> usize {
+        self.pieces.len()
+    }
+
+    /// Determine if no `Piece` is recorded in the `Solution`, like in the
+    /// `empty` one.
+    pub fn is_empty(&self) -> bool {
+        self.pieces.is_empty()
+    }
+
+    /// Iterate over the `Piece`s of the `Solution`, in the order they were
+    /// recorded.
+    pub fn iter(&self) -> impl Iterator<Item = &Piece<T>> {
+        self.pieces.iter()
+    }
+
+    /// The `Piece`s of the `Solution` with the names of the `Template`s they
+    /// were created from.
+    pub fn named_pieces(&self) -> Vec<(Option<&str>, &Piece<T>)> {
+        self.pieces.iter().map(|piece| (piece.name(), piece)).collect()
+    }
+
+    /// Map every covered `Position` to the index of the `Piece` that covers it,
+    /// in the order the `Piece`s were placed. A `Position` that is covered
+    /// twice maps to the `Piece` placed last, see `try_cell_map`.
+    pub fn cell_map(&self) -> HashMap<Position<T>, usize> where T: Hash + Ord {
+        self.pieces
+            .iter()
+            .enumerate()
+            .flat_map(|(index, piece)| piece.iter().map(move |position| (position, index)))
+            .collect()
+    }
+
+    /// Variant of the `cell_map` method that returns an `Overlap` when two
+    /// `Piece`s cover the same `Position`.
+    pub fn try_cell_map(&self) -> Result<HashMap<Position<T>, usize>, Overlap> where T: Hash + Ord {
+        let mut cells: HashMap<Position<T>, usize> = HashMap::new();
+        for (index, piece) in self.pieces.iter().enumerate() {
+            for position in piece.iter() {
+                if let Some(first) = cells.insert(position, index) {
+                    return Err(Overlap { first, second: index });
+                }
+            }
+        }
+        Ok(cells)
+    }
+
+    /// Map every covered `Position` to the name of the `Piece` that covers it,
+    /// or to its index when the `Piece` has no name. Copies of a `Template`
+    /// share their name, so only `cell_map` tells them apart.
+    pub fn labeled_cell_map(&self) -> HashMap<Position<T>, String> where T: Hash + Ord {
+        let labels: Vec<String> = self.pieces
+            .iter()
+            .enumerate()
+            .map(|(index, piece)| piece.name().map_or_else(|| index.to_string(), String::from))
+            .collect();
+        self.cell_map()
+            .into_iter()
+            .map(|(position, index)| (position, labels[index].clone()))
+            .collect()
+    }
+}
+
+/// Rotates or reflects all the `Piece`s of the `Solution` together. The
+/// recorded symmetries no longer apply, so they are forgotten.
+impl<T> Transformable for Solution<T> where T: Transformable + PartialOrd + Ord {
+    fn transform(&mut self, symmetry: &CubeSymmetry) {
+        for piece in &mut self.pieces {
+            piece.transform(symmetry);
+        }
+        for recorded in &mut self.symmetries {
+            *recorded = None;
+        }
+    }
+}
 
-        Solution { pieces }
+/// Moves all the `Piece`s of the `Solution` together.
+impl<T> Translatable<T> for Solution<T> where T: VectorAdd<T> {
+    fn translate(&mut self, translation: &Translation<T>) {
+        for piece in &mut self.pieces {
+            piece.translate(translation);
+        }
     }
 }
 
-impl Display for Solution<(i8, i8, i8)> {
+impl<N> Display for Solution<(N, N, N)> where N: Display {
     fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
         write!(f, "<")?;
         for piece in &self.pieces {
@@ -80,6 +646,43 @@ impl Display for Solution<(i8, i8, i8)> {
     }
 }
 
+impl Solution<(i8, i8)> {
+    /// Draw the `Solution` as a grid of characters, one row per line. Each
+    /// `Piece` is drawn with its own letter, `A`, `B`, `C` and so on, and cells
+    /// inside the bounding box that are not covered are drawn as `.`. The letter
+    /// of a `Piece` with a color is drawn in that color, with the escape codes
+    /// of a terminal that supports 24-bit color.
+    pub fn to_grid(&self) -> String {
+        const LETTERS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+        let cells = self.cell_map();
+        let positions: Vec<Position<(i8, i8)>> = cells.keys().cloned().collect();
+        let (minimum, maximum) = match bounding_box(&positions) {
+            Some(corners) => corners,
+            None => return String::new(),
+        };
+        let (min_x, min_y) = *minimum.coordinates();
+        let (max_x, max_y) = *maximum.coordinates();
+
+        let mut grid = String::new();
+        for y in min_y..=max_y {
+            for x in min_x..=max_x {
+                match cells.get(&Position::d2(x, y)) {
+                    Some(index) => {
+                        let letter = LETTERS[index % LETTERS.len()] as char;
+                        match self.pieces[*index].color() {
+                            Some(color) => grid.push_str(&format!("\x1b[38;2;{};{};{}m{}\x1b[0m", color.red, color.green, color.blue, letter)),
+                            None => grid.push(letter),
+                        }
+                    }
+                    None => grid.push('.'),
+                }
+            }
+            grid.push('\n');
+        }
+        grid
+    }
+}
+
 impl Display for Solution<(i8, i8)> {
     fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
         write!(f, "<")?;
@@ -91,90 +694,2243 @@ impl Display for Solution<(i8, i8)> {
 }
 
 
+/// Cheap necessary condition for the `Bag` to pack the `Target`: the volume of
+/// all the `Template`s together should equal the volume of the `Target`.
+pub fn is_feasible<T>(target: &Target<T>, bag: &Bag<T>) -> bool where T: PartialOrd + Ord + PartialEq + Eq + Clone {
+    target.volume() == bag.total_volume()
+}
+
+/// Check the necessary conditions for the `Bag` to pack the `Target`, without
+/// searching: neither the `Target` nor a `Template` is empty, the volume of
+/// the `Target` is between the volume of the `Piece`s that have to be used and
+/// the volume of all the `Piece`s, and the `Piece`s can balance the
+/// checkerboard coloring of the `Target`, see `prune_parity`. Returns the
+/// first condition that fails.
+pub fn validate(target: &Target<(i8, i8, i8)>, bag: &Bag<(i8, i8, i8)>) -> Result<(), ValidationError> {
+    if target.is_packed() {
+        return Err(ValidationError::EmptyTarget);
+    }
+    if bag.templates().any(|template| template.is_empty()) {
+        return Err(ValidationError::EmptyPiece);
+    }
+    let (volume, required, available) = (target.volume(), bag.required_volume(), bag.total_volume());
+    if volume < required || available < volume {
+        return Err(ValidationError::VolumeMismatch { target: volume, required, available });
+    }
+    if prune_parity(target, bag) {
+        return Err(ValidationError::ColorImbalance);
+    }
+    Ok(())
+}
+
 /// Attempt to pack all the `Piece`s in the `Bag` into the `Target` region. When
 /// a solution is found, the `when_solved` callback is called with that solution.
+/// `Piece`s with a `Bound::UpTo` may be left unused.
 pub fn solve<F, T>(target: &Target<T>, bag: Bag<T>, when_solved: &mut F) where F: (FnMut(Solution<T>)) + Sized, T: Clone + PartialOrd + Ord + Transformable + Normalizable<T> + VectorDifference<T> + VectorAdd<T> {
     let partial_solution: Solution<T> = Solution::empty();
     solve_with(target, bag, partial_solution, when_solved)
 }
 
+/// Variant of the `solve` method that returns an error, instead of panicking,
+/// when the `Target` or a `Template` in the `Bag` has no cells. The input is
+/// validated before the search starts, after which the search can not fail.
+pub fn try_solve<F, T>(target: &Target<T>, bag: Bag<T>, when_solved: &mut F) -> Result<(), SolverError> where F: (FnMut(Solution<T>)) + Sized, T: Clone + PartialOrd + Ord + Transformable + Normalizable<T> + VectorDifference<T> + VectorAdd<T> {
+    if target.is_packed() {
+        return Err(SolverError::EmptyTarget);
+    }
+    if bag.templates().any(|template| template.is_empty()) {
+        return Err(SolverError::EmptyPiece);
+    }
+    solve(target, bag, when_solved);
+    Ok(())
+}
 
 /// Variant of the `solve` method that allows for a different starting point.
 pub fn solve_with<F, T>(target: &Target<T>, bag: Bag<T>, partial_solution: Solution<T>, when_solved: &mut F) where F: (FnMut(Solution<T>)) + Sized, T: Clone + PartialOrd + Ord + Transformable + Normalizable<T> + VectorDifference<T> + VectorAdd<T> {
-    if target.is_packed() {
-        when_solved(partial_solution)
-    } else {
-        let open_position = target.minimum_position().unwrap();
-        for (template, rest_of_bag) in bag {
-            for mut piece in template {
-                let block = piece.minimum_position().unwrap();
-                let translation = block.to(&open_position);
-                piece.translate(&translation);
-                if target.fits(&piece) {
-                    let remaining_target = target.place(&piece);
-                    let candidate_solution = partial_solution.record(&piece);
-                    solve_with(&remaining_target, rest_of_bag.clone(), candidate_solution, when_solved)
-                }
-            }
-        }
-    }
+    let mut context = Search::new(SolveOptions::default());
+    let _ = search(target, bag, partial_solution, &mut context, &mut |solution|{
+        when_solved(solution);
+        ControlFlow::<()>::Continue(())
+    });
 }
 
-#[cfg(test)]
-mod tests {
-    use std::fmt::Write;
-    use super::super::piece::{Position, Piece, Template};
-    use super::super::pieces::Bag;
-    use super::*;
+/// Variant of the `solve` method that can be aborted. The search stops as soon
+/// as `cancel` is set, which can be done from an other thread or from the
+/// `when_solved` callback.
+pub fn solve_cancellable<F, T>(target: &Target<T>, bag: Bag<T>, cancel: &AtomicBool, when_solved: &mut F) where F: (FnMut(Solution<T>)) + Sized, T: Clone + PartialOrd + Ord + Transformable + Normalizable<T> + VectorDifference<T> + VectorAdd<T> {
+    let partial_solution: Solution<T> = Solution::empty();
+    solve_with_cancellable(target, bag, partial_solution, cancel, when_solved)
+}
 
-    #[test]
-    fn piece_should_fit_in_target() {
-        let target = Target::new(vec!(
-            Position::new(0, 0, 0),
-            Position::new(1, 0, 0),
-            Position::new(0, 1, 0),
-            Position::new(1, 1, 0),
-            Position::new(0, 0, 1),
-            Position::new(1, 0, 1),
-            Position::new(0, 1, 1),
-            Position::new(1, 1, 1),
-        ));
+/// Variant of the `solve_with` method that can be aborted by setting `cancel`.
+pub fn solve_with_cancellable<F, T>(target: &Target<T>, bag: Bag<T>, partial_solution: Solution<T>, cancel: &AtomicBool, when_solved: &mut F) where F: (FnMut(Solution<T>)) + Sized, T: Clone + PartialOrd + Ord + Transformable + Normalizable<T> + VectorDifference<T> + VectorAdd<T> {
+    let mut context = Search::cancellable(SolveOptions::default(), cancel);
+    let _ = search(target, bag, partial_solution, &mut context, &mut |solution|{
+        when_solved(solution);
+        ControlFlow::<()>::Continue(())
+    });
+}
 
-        let piece = Piece::new(vec!(
-            Position::new(0, 0, 0),
-            Position::new(1, 0, 0),
-            Position::new(0, 1, 0),
-            Position::new(0, 0, 1),
-        ));
+/// Find a single way to pack the `Piece`s in the `Bag` into the `Target`. The
+/// search stops as soon as a packing is found. Returns `None` when there is no
+/// packing at all.
+pub fn first_solution<T>(target: &Target<T>, bag: Bag<T>) -> Option<Solution<T>> where T: Clone + PartialOrd + Ord + Transformable + Normalizable<T> + VectorDifference<T> + VectorAdd<T> {
+    let mut context = Search::new(SolveOptions::default());
+    match search(target, bag, Solution::empty(), &mut context, &mut ControlFlow::Break) {
+        ControlFlow::Break(solution) => Some(solution),
+        ControlFlow::Continue(()) => None,
+    }
+}
 
-        assert!(target.fits(&piece));
+/// Collect every way to pack the `Piece`s in the `Bag` into the `Target`, in
+/// the order `solve` finds them.
+pub fn all_solutions<T>(target: &Target<T>, bag: Bag<T>) -> Vec<Solution<T>> where T: Clone + PartialOrd + Ord + Transformable + Normalizable<T> + VectorDifference<T> + VectorAdd<T> {
+    let mut solutions: Vec<Solution<T>> = vec!();
+    solve(target, bag, &mut |solution| solutions.push(solution));
+    solutions
+}
+
+/// Determine if the `Piece`s in the `Bag` can pack the `Target`. The search
+/// stops as soon as a packing is found, and no `Solution` is constructed.
+pub fn has_solution<T>(target: &Target<T>, bag: Bag<T>) -> bool where T: Clone + PartialOrd + Ord + Transformable + Normalizable<T> + VectorDifference<T> + VectorAdd<T> {
+    let mut context = Search::new(SolveOptions::default());
+    search(target, bag, (), &mut context, &mut ControlFlow::Break).is_break()
+}
+
+/// Variant of the `solve` method that stops the search once `limit` solutions
+/// have been delivered to the `when_solved` callback. Returns the number of
+/// solutions delivered, which is less than `limit` when there are no more.
+pub fn solve_at_most<F, T>(target: &Target<T>, bag: Bag<T>, limit: usize, when_solved: &mut F) -> usize where F: (FnMut(Solution<T>)) + Sized, T: Clone + PartialOrd + Ord + Transformable + Normalizable<T> + VectorDifference<T> + VectorAdd<T> {
+    if limit == 0 {
+        return 0;
     }
+    let mut context = Search::new(SolveOptions::default());
+    let mut delivered: usize = 0;
+    let _ = search(target, bag, Solution::empty(), &mut context, &mut |solution|{
+        when_solved(solution);
+        delivered += 1;
+        if delivered < limit { ControlFlow::Continue(()) } else { ControlFlow::Break(()) }
+    });
+    delivered
+}
 
-    #[test]
-    fn solve_should_pack_pieces() {
-        let target = Target::new(vec!(
-            Position::new(0, 0, 0),
-            Position::new(1, 0, 0),
-            Position::new(0, 1, 0),
-            Position::new(1, 1, 0),
-            Position::new(0, 0, 1),
-            Position::new(1, 0, 1),
-            Position::new(0, 1, 1),
-            Position::new(1, 1, 1),
-        ));
+/// Iterate over the ways the `Piece`s in the `Bag` can pack the `Target`, in
+/// the same order as `solve` finds them. The search runs on its own thread and
+/// hands over one `Solution` at a time, so it only explores the search tree as
+/// far as needed for the next `Solution`. Dropping the iterator stops the
+/// search.
+pub fn solutions<T>(target: &Target<T>, bag: Bag<T>) -> SolutionIterator<T> where T: Send + 'static + Clone + PartialOrd + Ord + Transformable + Normalizable<T> + VectorDifference<T> + VectorAdd<T> {
+    let target = target.clone();
+    let (sender, receiver) = mpsc::sync_channel(0);
+    thread::spawn(move || {
+        let mut context = Search::new(SolveOptions::default());
+        let _ = search(&target, bag, Solution::empty(), &mut context, &mut |solution|{
+            match sender.send(solution) {
+                Ok(()) => ControlFlow::Continue(()),
+                Err(_) => ControlFlow::Break(()),
+            }
+        });
+    });
+    SolutionIterator { receiver }
+}
 
-        let bag = Bag::new(vec!(
-            (2,Template::new(vec!(
-                Position::new(0, 0, 0),
-                Position::new(1, 0, 0),
-                Position::new(0, 1, 0),
-                Position::new(0, 0, 1),
-            ))),
-        ));
+/// Iterator over the `Solution`s of a packing problem, see `solutions`.
+pub struct SolutionIterator<T> {
+    receiver: Receiver<Solution<T>>,
+}
 
-        let mut solutions: Vec<Solution<(i8, i8, i8)>> = vec!();
-        solve(&target, bag, &mut |solution|{ solutions.push(solution)});
-        assert_eq!(solutions.len(), 4);
+impl<T> Iterator for SolutionIterator<T> {
+    type Item = Solution<T>;
+
+    fn next(&mut self) -> Option<Solution<T>> {
+        self.receiver.recv().ok()
+    }
+}
+
+/// Variant of the `solve` method that sends every `Solution` through a channel,
+/// so it can be consumed on another thread. The search stops early when the
+/// receiving end of the channel hangs up.
+pub fn solve_channel<T>(target: &Target<T>, bag: Bag<T>, sender: Sender<Solution<T>>) where T: Clone + PartialOrd + Ord + Transformable + Normalizable<T> + VectorDifference<T> + VectorAdd<T> {
+    let mut context = Search::new(SolveOptions::default());
+    let _ = search(target, bag, Solution::empty(), &mut context, &mut |solution|{
+        match sender.send(solution) {
+            Ok(()) => ControlFlow::Continue(()),
+            Err(_) => ControlFlow::Break(()),
+        }
+    });
+}
+
+/// Optional checks the solver performs at every node of the search. These do
+/// not change the solutions that are found, only how much work it takes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SolveOptions {
+    /// Prune a branch when no remaining `Template` can cover the open `Position`.
+    pub check_open_position: bool,
+    /// Prune a branch when *any* open `Position` can not be covered by a
+    /// remaining `Template`. This implies `check_open_position`.
+    pub forward_check: bool,
+    /// Treat equal `Template`s in the `Bag` as interchangeable copies. Packings
+    /// that only differ in which copy is used where are explored once, because
+    /// the copies are placed in the order of the `Position`s they cover.
+    pub break_identical_symmetry: bool,
+    /// When no remaining `Template` is smaller than the open region, a single
+    /// `Piece` has to fill it exactly. Check that directly, instead of
+    /// descending into the search tree.
+    pub endgame: bool,
+    /// The order in which the open `Position`s are covered. It does not change
+    /// the solutions that are found, but the size of the search tree depends
+    /// on it.
+    pub scan_order: ScanOrder,
+}
+
+/// The order in which the open `Position`s of a `Target` are covered, by
+/// naming the coordinates from the most to the least significant. The solver
+/// covers the first open `Position` in this order.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ScanOrder {
+    /// Compare x first, then y, then z. This is the order of `Position`s.
+    #[default]
+    Xyz,
+    /// Compare z first, then y, then x. In two dimensions, y is compared
+    /// before x.
+    Zyx,
+}
+
+impl ScanOrder {
+    /// Compare two `Position`s in this order.
+    pub fn compare<T>(&self, left: &Position<T>, right: &Position<T>) -> cmp::Ordering where T: Clone + Ord + Transformable {
+        match self {
+            ScanOrder::Xyz => left.cmp(right),
+            ScanOrder::Zyx => {
+                let symmetry = T::reversing_symmetry();
+                let (mut left, mut right) = (left.clone(), right.clone());
+                left.transform(&symmetry);
+                right.transform(&symmetry);
+                left.cmp(&right)
+            }
+        }
+    }
+}
+
+/// Statistics gathered during a search.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SolveStats {
+    /// Number of nodes of the search tree that were visited.
+    pub nodes_visited: usize,
+    /// Number of placements of a `Piece` that were checked against the `Target`.
+    pub placements_tried: usize,
+    /// Number of solutions that were found.
+    pub solutions_found: usize,
+    /// Largest number of `Piece`s placed on the way to a node.
+    pub max_depth: usize,
+}
+
+/// Variant of the `solve` method that performs the checks selected by the
+/// `SolveOptions`. Returns the `SolveStats` of the search.
+pub fn solve_with_options<F, T>(target: &Target<T>, bag: Bag<T>, options: SolveOptions, when_solved: &mut F) -> SolveStats where F: (FnMut(Solution<T>)) + Sized, T: Clone + PartialOrd + Ord + Transformable + Normalizable<T> + VectorDifference<T> + VectorAdd<T> {
+    let bag = if options.break_identical_symmetry { bag.merge_identical() } else { bag };
+    let mut context = Search::new(options);
+    let _ = search(target, bag, Solution::empty(), &mut context, &mut |solution|{
+        when_solved(solution);
+        ControlFlow::<()>::Continue(())
+    });
+    context.stats
+}
+
+/// How the solver chooses the open `Position` to cover next.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SolveStrategy {
+    /// Cover the smallest open `Position`. Only the `Piece`s that have their own
+    /// smallest `Position` there need to be tried.
+    #[default]
+    LexMin,
+    /// Cover the open `Position` with the fewest placements of the remaining
+    /// `Template`s. Determining that `Position` is costly at every node, but it
+    /// tends to prune the search tree early.
+    MostConstrained,
+}
+
+/// Variant of the `solve` method that covers the open `Position`s in the order
+/// determined by the `SolveStrategy`. Returns the `SolveStats` of the search.
+pub fn solve_with_strategy<F, T>(target: &Target<T>, bag: Bag<T>, strategy: SolveStrategy, when_solved: &mut F) -> SolveStats where F: (FnMut(Solution<T>)) + Sized, T: Clone + PartialOrd + Ord + Transformable + Normalizable<T> + VectorDifference<T> + VectorAdd<T> {
+    let mut context = Search::new(SolveOptions::default());
+    context.strategy = strategy;
+    let _ = search(target, bag, Solution::empty(), &mut context, &mut |solution|{
+        when_solved(solution);
+        ControlFlow::<()>::Continue(())
+    });
+    context.stats
+}
+
+/// Variant of the `solve` method that abandons every branch of the search for
+/// which `prune` returns `true`. `prune` is called with the remaining `Target`
+/// and `Bag` at every node that still has cells to pack. Returns the
+/// `SolveStats` of the search.
+pub fn solve_with_pruning<F, P, T>(target: &Target<T>, bag: Bag<T>, prune: P, when_solved: &mut F) -> SolveStats where F: (FnMut(Solution<T>)) + Sized, P: Fn(&Target<T>, &Bag<T>) -> bool, T: Clone + PartialOrd + Ord + Transformable + Normalizable<T> + VectorDifference<T> + VectorAdd<T> {
+    let mut context = Search::new(SolveOptions::default());
+    context.prune = Some(&prune);
+    let _ = search(target, bag, Solution::empty(), &mut context, &mut |solution|{
+        when_solved(solution);
+        ControlFlow::<()>::Continue(())
+    });
+    context.stats
+}
+
+/// Prune for `solve_with_pruning` that rejects a `Target` with a connected
+/// region that is smaller than every `Template` left in the `Bag`. No `Piece`
+/// will ever fill such a cavity.
+pub fn prune_cavities(target: &Target<(i8, i8, i8)>, bag: &Bag<(i8, i8, i8)>) -> bool {
+    match bag.templates().map(|template| template.len()).min() {
+        Some(smallest) => target.connected_components().iter().any(|component| component.volume() < smallest),
+        None => false,
+    }
+}
+
+/// Prune for `solve_with_pruning` that rejects a `Target` whose checkerboard
+/// balance can not be matched by the `Template`s left in the `Bag`. Every
+/// `Piece` contributes either its balance or its negation, depending on where
+/// it is placed. This only prunes when all the `Template`s have to be used,
+/// i.e. when their volume equals the volume of the `Target`.
+pub fn prune_parity(target: &Target<(i8, i8, i8)>, bag: &Bag<(i8, i8, i8)>) -> bool {
+    if target.volume() != bag.total_volume() {
+        return false;
+    }
+    let mut balances: HashSet<i32> = HashSet::new();
+    balances.insert(0);
+    for (count, template) in bag.iter() {
+        let balance = Piece::from(template.clone()).color_balance();
+        for _ in 0..count {
+            balances = balances.iter().flat_map(|sum| [sum + balance, sum - balance]).collect();
+        }
+    }
+    !balances.contains(&target.color_balance())
+}
+
+/// Whether a search with a budget explored the whole search tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolveStatus {
+    /// Every solution was found.
+    Complete,
+    /// The budget ran out, there may be more solutions.
+    Incomplete,
+}
+
+/// Variant of the `solve` method that visits at most `max_nodes` nodes of the
+/// search tree. Returns whether the search completed, together with the
+/// solutions found so far.
+pub fn solve_budgeted<T>(target: &Target<T>, bag: Bag<T>, max_nodes: usize) -> (SolveStatus, Vec<Solution<T>>) where T: Clone + PartialOrd + Ord + Transformable + Normalizable<T> + VectorDifference<T> + VectorAdd<T> {
+    let mut context = Search::new(SolveOptions::default());
+    context.node_budget = Some(max_nodes);
+    let mut solutions: Vec<Solution<T>> = vec!();
+    let _ = search(target, bag, Solution::empty(), &mut context, &mut |solution|{
+        solutions.push(solution);
+        ControlFlow::<()>::Continue(())
+    });
+    let status = if context.exhausted { SolveStatus::Incomplete } else { SolveStatus::Complete };
+    (status, solutions)
+}
+
+/// Variant of the `solve` method that gathers `SolveStats` while searching.
+/// Returns the `SolveStats` once the search is complete.
+pub fn solve_with_stats<F, T>(target: &Target<T>, bag: Bag<T>, when_solved: &mut F) -> SolveStats where F: (FnMut(Solution<T>)) + Sized, T: Clone + PartialOrd + Ord + Transformable + Normalizable<T> + VectorDifference<T> + VectorAdd<T> {
+    solve_with_options(target, bag, SolveOptions::default(), when_solved)
+}
+
+/// How often the orientations of the `Template`s are used in the solutions.
+#[derive(Debug, Clone)]
+pub struct OrientationStats<T> {
+    /// For the index of a `Template` in the `Bag` together with one of its
+    /// orientations, moved to the origin, the number of times a `Piece` in that
+    /// orientation was placed.
+    pub tally: HashMap<(usize, Piece<T>), usize>,
+    /// Number of placed `Piece`s that could not be attributed to a `Template`.
+    pub unmatched: usize,
+}
+
+/// Variant of the `solve` method that also tallies how often every orientation
+/// of every `Template` is used in the solutions. A placed `Piece` is attributed
+/// to the `Template` that its recorded `CubeSymmetry` orients like the `Piece`,
+/// or, without a recorded symmetry, to the first `Template` in the `Bag` that
+/// has its orientation. Returns the `OrientationStats` of the solutions.
+pub fn solve_with_orientation_stats<F, T>(target: &Target<T>, bag: Bag<T>, when_solved: &mut F) -> OrientationStats<T> where F: (FnMut(Solution<T>)) + Sized, T: Hash + Clone + PartialOrd + Ord + Transformable + Normalizable<T> + VectorDifference<T> + VectorAdd<T> {
+    let templates: Vec<Piece<T>> = bag.templates().cloned().map(Piece::from).collect();
+    let orientations: Vec<Vec<Piece<T>>> = bag.templates().map(|template| template.into_iter().collect()).collect();
+    let mut stats = OrientationStats { tally: HashMap::new(), unmatched: 0 };
+    solve(target, bag, &mut |solution: Solution<T>|{
+        for (piece, symmetry) in solution.pieces.iter().zip(&solution.symmetries) {
+            let orientation = piece.normalized();
+            let index = match symmetry {
+                Some(symmetry) => templates.iter().position(|template| template.transformed(symmetry).normalized() == orientation),
+                None => orientations.iter().position(|pieces| pieces.contains(&orientation)),
+            };
+            match index {
+                Some(index) => *stats.tally.entry((index, orientation)).or_insert(0) += 1,
+                None => stats.unmatched += 1,
+            }
+        }
+        when_solved(solution)
+    });
+    stats
+}
+
+/// Variant of the `solve` method that searches the branches of the first
+/// `Piece` that is placed in parallel, on the rayon thread pool.
+///
+/// *Note* the subtrees are searched independently, so solutions arrive in a
+/// nondeterministic order. The `when_solved` callback is called from the worker
+/// threads, one solution at a time.
+#[cfg(feature = "rayon")]
+pub fn solve_parallel<F, T>(target: &Target<T>, bag: Bag<T>, when_solved: &mut F) where F: (FnMut(Solution<T>)) + Send, T: Send + Sync + Clone + PartialOrd + Ord + Transformable + Normalizable<T> + VectorDifference<T> + VectorAdd<T> {
+    use rayon::prelude::*;
+    use std::sync::Mutex;
+
+    let open_position = match target.minimum_position() {
+        Some(open_position) => open_position,
+        None if bag.is_satisfied() => return when_solved(Solution::empty()),
+        None => return,
+    };
+    let mut branches: Vec<(Target<T>, Bag<T>, Solution<T>)> = vec!();
+    for (template, rest_of_bag) in bag {
+        let mut pieces = template.into_iter();
+        while let Some((symmetry, mut piece)) = pieces.next_with_symmetry() {
+            let block = piece.minimum_position().unwrap();
+            piece.translate(&block.to(&open_position));
+            if target.fits(&piece) {
+                branches.push((target.place(&piece), rest_of_bag.clone(), Solution::empty().record_with_symmetry(&piece, Some(symmetry))));
+            }
+        }
+    }
+    let when_solved = Mutex::new(when_solved);
+    branches.into_par_iter().for_each(|(remaining_target, rest_of_bag, partial_solution)| {
+        let mut context = Search::new(SolveOptions::default());
+        let _ = search(&remaining_target, rest_of_bag, partial_solution, &mut context, &mut |solution|{
+            (when_solved.lock().unwrap())(solution);
+            ControlFlow::<()>::Continue(())
+        });
+    });
+}
+
+/// Decides if a branch of the search can be abandoned.
+type Prune<'a, T> = &'a dyn Fn(&Target<T>, &Bag<T>) -> bool;
+
+/// State shared by all the nodes of a search.
+struct Search<'a, T> {
+    options: SolveOptions,
+    strategy: SolveStrategy,
+    prune: Option<Prune<'a, T>>,
+    stats: SolveStats,
+    depth: usize,
+    cancel: Option<&'a AtomicBool>,
+    node_budget: Option<usize>,
+    exhausted: bool,
+}
+
+impl<'a, T> Search<'a, T> {
+    fn new(options: SolveOptions) -> Self {
+        Search { options, strategy: SolveStrategy::default(), prune: None, stats: SolveStats::default(), depth: 0, cancel: None, node_budget: None, exhausted: false }
+    }
+
+    fn cancellable(options: SolveOptions, cancel: &'a AtomicBool) -> Self {
+        Search { options, strategy: SolveStrategy::default(), prune: None, stats: SolveStats::default(), depth: 0, cancel: Some(cancel), node_budget: None, exhausted: false }
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.exhausted || self.cancel.is_some_and(|cancel| cancel.load(Ordering::Relaxed))
+    }
+
+    /// The `Position` that comes first in the `ScanOrder` of the options.
+    fn minimum(&self, positions: &[Position<T>]) -> Option<Position<T>> where T: Ord + Clone + Transformable {
+        match self.options.scan_order {
+            ScanOrder::Xyz => positions.iter().min().cloned(),
+            order => positions.iter().min_by(|left, right| order.compare(left, right)).cloned(),
+        }
+    }
+}
+
+fn search<F, B, P, T>(target: &Target<T>, bag: Bag<T>, partial_solution: P, context: &mut Search<T>, when_solved: &mut F) -> ControlFlow<B> where F: (FnMut(P) -> ControlFlow<B>) + Sized, P: Partial<T>, T: Clone + PartialOrd + Ord + Transformable + Normalizable<T> + VectorDifference<T> + VectorAdd<T> {
+    if context.is_cancelled() {
+        return ControlFlow::Continue(());
+    }
+    if context.node_budget.is_some_and(|budget| context.stats.nodes_visited >= budget) {
+        context.exhausted = true;
+        return ControlFlow::Continue(());
+    }
+    context.stats.nodes_visited += 1;
+    context.stats.max_depth = context.stats.max_depth.max(context.depth);
+    let options = context.options;
+    if target.is_packed() {
+        if !bag.is_satisfied() {
+            return ControlFlow::Continue(());
+        }
+        context.stats.solutions_found += 1;
+        when_solved(partial_solution)
+    } else {
+        if context.prune.is_some_and(|prune| prune(target, &bag)) {
+            return ControlFlow::Continue(());
+        }
+        let open_position = context.minimum(&target.collection).unwrap();
+        if options.forward_check {
+            if !target.collection.iter().all(|position| target.is_coverable(position, &bag)) {
+                return ControlFlow::Continue(());
+            }
+        } else if options.check_open_position && !target.is_coverable(&open_position, &bag) {
+            return ControlFlow::Continue(());
+        }
+        let volume = target.collection.len();
+        if options.endgame && bag.templates().all(|template| template.len() >= volume) {
+            for (template, rest_of_bag) in bag {
+                if template.len() == volume && rest_of_bag.is_satisfied() {
+                    let mut pieces = template.into_iter();
+                    while let Some((symmetry, mut piece)) = pieces.next_with_symmetry() {
+                        let block = context.minimum(piece.positions()).unwrap();
+                        piece.translate(&block.to(&open_position));
+                        context.stats.placements_tried += 1;
+                        if target.fits(&piece) {
+                            context.stats.solutions_found += 1;
+                            when_solved(partial_solution.record(&piece, Some(symmetry)))?;
+                            if context.is_cancelled() {
+                                return ControlFlow::Continue(());
+                            }
+                        }
+                    }
+                }
+            }
+            return ControlFlow::Continue(());
+        }
+        match context.strategy {
+            SolveStrategy::LexMin => {
+                for (template, rest_of_bag) in bag {
+                    let mut pieces = template.into_iter();
+                    while let Some((symmetry, mut piece)) = pieces.next_with_symmetry() {
+                        let block = context.minimum(piece.positions()).unwrap();
+                        let translation = block.to(&open_position);
+                        piece.translate(&translation);
+                        context.stats.placements_tried += 1;
+                        if target.fits(&piece) {
+                            descend(target, &rest_of_bag, &partial_solution, &piece, Some(symmetry), context, when_solved)?;
+                            if context.is_cancelled() {
+                                return ControlFlow::Continue(());
+                            }
+                        }
+                    }
+                }
+            }
+            SolveStrategy::MostConstrained => {
+                let cell = target.collection
+                    .iter()
+                    .min_by_key(|position| bag.templates().map(|template| target.placements_covering(position, template).len()).sum::<usize>())
+                    .unwrap()
+                    .clone();
+                for (template, rest_of_bag) in bag {
+                    for piece in target.placements_covering(&cell, &template) {
+                        context.stats.placements_tried += 1;
+                        descend(target, &rest_of_bag, &partial_solution, &piece, None, context, when_solved)?;
+                        if context.is_cancelled() {
+                            return ControlFlow::Continue(());
+                        }
+                    }
+                }
+            }
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+/// Place a `Piece` that fits in the `Target` and search the remaining `Target`.
+fn descend<F, B, P, T>(target: &Target<T>, rest_of_bag: &Bag<T>, partial_solution: &P, piece: &Piece<T>, symmetry: Option<CubeSymmetry>, context: &mut Search<T>, when_solved: &mut F) -> ControlFlow<B> where F: (FnMut(P) -> ControlFlow<B>) + Sized, P: Partial<T>, T: Clone + PartialOrd + Ord + Transformable + Normalizable<T> + VectorDifference<T> + VectorAdd<T> {
+    let remaining_target = target.place(piece);
+    let candidate_solution = partial_solution.record(piece, symmetry);
+    context.depth += 1;
+    let flow = search(&remaining_target, rest_of_bag.clone(), candidate_solution, context, when_solved);
+    context.depth -= 1;
+    flow
+}
+
+/// What the search keeps track of on the way down to a packed `Target`.
+trait Partial<T> {
+    /// Record a `Piece` that is placed in the `Target`, with the `CubeSymmetry`
+    /// that oriented it, if known.
+    fn record(&self, piece: &Piece<T>, symmetry: Option<CubeSymmetry>) -> Self;
+}
+
+impl<T> Partial<T> for Solution<T> where T: Clone {
+    fn record(&self, piece: &Piece<T>, symmetry: Option<CubeSymmetry>) -> Self {
+        Solution::record_with_symmetry(self, piece, symmetry)
+    }
+}
+
+/// Keeps track of nothing, so no `Piece`s are cloned while searching.
+impl<T> Partial<T> for () {
+    fn record(&self, _piece: &Piece<T>, _symmetry: Option<CubeSymmetry>) -> Self {}
+}
+
+/// Count the number of ways the `Piece`s in the `Bag` can pack the `Target`.
+/// This walks the same search tree as `solve`, but it does not construct the
+/// `Solution`s.
+pub fn count_solutions<T>(target: &Target<T>, bag: Bag<T>) -> usize where T: Clone + PartialOrd + Ord + Transformable + Normalizable<T> + VectorDifference<T> + VectorAdd<T> {
+    let mut context = Search::new(SolveOptions::default());
+    let mut count: usize = 0;
+    let _ = search(target, bag, (), &mut context, &mut |()|{
+        count += 1;
+        ControlFlow::<()>::Continue(())
+    });
+    count
+}
+
+/// Variant of the `solve` method that writes every `Solution` to `writer` as
+/// soon as it is found, as a single line of JSON
+/// ([NDJSON](http://ndjson.org/)). The writer is flushed periodically, so a
+/// consumer can process solutions while the search is still running.
+///
+/// Returns the number of solutions written. Writing stops at the first error,
+/// which is then returned.
+#[cfg(feature = "serde")]
+pub fn solve_to_ndjson<W, T>(target: &Target<T>, bag: Bag<T>, writer: &mut W) -> io::Result<usize> where W: Write, T: serde::Serialize + Clone + PartialOrd + Ord + Transformable + Normalizable<T> + VectorDifference<T> + VectorAdd<T> {
+    const FLUSH_INTERVAL: usize = 64;
+    let mut count: usize = 0;
+    let mut result: io::Result<()> = Ok(());
+    solve(target, bag, &mut |solution|{
+        if result.is_ok() {
+            result = serde_json::to_writer(&mut *writer, &solution)
+                .map_err(io::Error::from)
+                .and_then(|_| writeln!(writer))
+                .and_then(|_| {
+                    count += 1;
+                    if count % FLUSH_INTERVAL == 0 { writer.flush() } else { Ok(()) }
+                });
+        }
+    });
+    result?;
+    writer.flush()?;
+    Ok(count)
+}
+
+/// Variant of the `solve` method that places the `Template`s strictly in the
+/// order they appear in the `Bag`.
+///
+/// A `Template` is only considered once every copy of the `Template`s before it
+/// is placed. Because the placement order is fixed, each `Piece` can go anywhere
+/// in the `Target`, not just cover the first open `Position`. This mirrors how a
+/// puzzle is assembled by hand and can be used to check if a given assembly
+/// order is feasible. *Note* a packing that uses identical copies of a
+/// `Template` is reported once for every way of assigning the copies.
+pub fn solve_ordered<F, T>(target: &Target<T>, bag: Bag<T>, when_solved: &mut F) where F: (FnMut(Solution<T>)) + Sized, T: Clone + PartialOrd + Ord + Transformable + Normalizable<T> + VectorDifference<T> + VectorAdd<T> {
+    let partial_solution: Solution<T> = Solution::empty();
+    solve_ordered_with(target, bag, partial_solution, &mut SolveStats::default(), when_solved)
+}
+
+/// Variant of the `solve_ordered` method that first sorts the `Template`s by
+/// the number of their placements in the `Target`, fewest first. Branching on
+/// the `Template` that is hardest to place keeps the search tree narrow near
+/// the root. Returns the `SolveStats` of the search.
+pub fn solve_hardest_first<F, T>(target: &Target<T>, bag: Bag<T>, when_solved: &mut F) -> SolveStats where F: (FnMut(Solution<T>)) + Sized, T: Clone + PartialOrd + Ord + Transformable + Normalizable<T> + VectorDifference<T> + VectorAdd<T> {
+    let bag = bag.sorted_by_key(|template| target.placements(template).len());
+    let mut stats = SolveStats::default();
+    solve_ordered_with(target, bag, Solution::empty(), &mut stats, when_solved);
+    stats
+}
+
+fn solve_ordered_with<F, T>(target: &Target<T>, bag: Bag<T>, partial_solution: Solution<T>, stats: &mut SolveStats, when_solved: &mut F) where F: (FnMut(Solution<T>)) + Sized, T: Clone + PartialOrd + Ord + Transformable + Normalizable<T> + VectorDifference<T> + VectorAdd<T> {
+    stats.nodes_visited += 1;
+    stats.max_depth = stats.max_depth.max(partial_solution.pieces.len());
+    if target.is_packed() {
+        if bag.is_satisfied() {
+            stats.solutions_found += 1;
+            when_solved(partial_solution)
+        }
+    } else if let Some((template, rest_of_bag)) = bag.split_first() {
+        let mut pieces = template.into_iter();
+        while let Some((symmetry, piece)) = pieces.next_with_symmetry() {
+            let block = piece.minimum_position().unwrap();
+            for open_position in &target.collection {
+                let mut candidate = piece.clone();
+                candidate.translate(&block.to(open_position));
+                stats.placements_tried += 1;
+                if target.fits(&candidate) {
+                    let remaining_target = target.place(&candidate);
+                    let candidate_solution = partial_solution.record_with_symmetry(&candidate, Some(symmetry));
+                    solve_ordered_with(&remaining_target, rest_of_bag.clone(), candidate_solution, stats, when_solved)
+                }
+            }
+        }
+    }
+}
+
+/// Variant of the `solve_unique` method that also treats equal `Template`s in the
+/// `Bag` as interchangeable. Packings that only differ in which copy of a
+/// `Template` goes where, or that are mapped onto each other by a symmetry of
+/// the `Target`, are reported once. Equal `Template`s are combined into a single
+/// entry before solving, so the copies are placed in a fixed order.
+/// `Piece`s with different names are told apart, even when they have the same
+/// shape. Use `Bag::deduplicate` beforehand to combine `Template`s that are
+/// only the same shape.
+pub fn solve_canonical<F, I, T>(target: &Target<T>, bag: Bag<T>, symmetries: I, when_solved: &mut F) where F: (FnMut(Solution<T>)) + Sized, I: IntoIterator<Item=CubeSymmetry>, T: Clone + PartialOrd + Ord + Transformable + Normalizable<T> + VectorDifference<T> + VectorAdd<T> {
+    let images = target.images(symmetries);
+    solve(target, bag.merge_identical(), &mut |solution|{
+        let key = solution.labeled_key(|_| {});
+        let smallest = images.iter().all(|(symmetry, translation)| {
+            key <= solution.labeled_key(|position| {
+                position.transform(symmetry);
+                position.translate(translation);
+            })
+        });
+        if smallest {
+            when_solved(solution)
+        }
+    });
+}
+
+/// Variant of the `solve` method that reports a single representative for
+/// every class of solutions that are mapped onto each other by a symmetry of the
+/// `Target`.
+///
+/// The symmetry group is supplied explicitly by `symmetries`. A symmetry that
+/// does not map the `Target` onto itself is ignored, so the symmetry group of
+/// the `Target` is detected automatically when all the rotations of the cube
+/// are supplied, e.g. with `CubeSymmetryIterator::rotations_only()`. The remaining
+/// symmetries should form a group, which is the case when either all rotations
+/// or all 48 symmetries are supplied. A `Solution` is reported when it is the
+/// smallest of all its images.
+pub fn solve_unique<F, I, T>(target: &Target<T>, bag: Bag<T>, symmetries: I, when_solved: &mut F) where F: (FnMut(Solution<T>)) + Sized, I: IntoIterator<Item=CubeSymmetry>, T: Clone + PartialOrd + Ord + Transformable + Normalizable<T> + VectorDifference<T> + VectorAdd<T> {
+    let images = target.images(symmetries);
+    solve(target, bag, &mut |solution|{
+        let key = solution.key(|_| {});
+        let smallest = images.iter().all(|(symmetry, translation)| {
+            key <= solution.key(|position| {
+                position.transform(symmetry);
+                position.translate(translation);
+            })
+        });
+        if smallest {
+            when_solved(solution)
+        }
+    });
+}
+
+/// Variant of the `solve` method that places the first `Template` of the `Bag`
+/// first, and only in placements that are the smallest of their images under
+/// the symmetries of the `Target`. Every other placement is an image of one of
+/// those, so every class of solutions is still reported, but the search is cut
+/// short at the root. When the `Template` may stay unused, the packings without
+/// it are searched the same way, fixing the next `Template`.
+///
+/// The symmetries are supplied like they are for `solve_unique`: those that
+/// do not map the `Target` onto itself are ignored, so supplying all the
+/// rotations of the cube with `CubeSymmetryIterator::rotations_only()` derives the
+/// symmetry group of the `Target`, as does supplying `Target::symmetries`.
+/// Of the copies of the fixed `Template`, the one with the smallest
+/// `Position`s takes the fixed role, so a packing is not reported once per
+/// copy. *Note* a class is still reported more than once when the placement of
+/// the fixed `Template` is symmetric itself.
+pub fn solve_fixing_first<F, I, T>(target: &Target<T>, bag: Bag<T>, symmetries: I, when_solved: &mut F) where F: (FnMut(Solution<T>)) + Sized, I: IntoIterator<Item=CubeSymmetry>, T: Clone + PartialOrd + Ord + Transformable + Normalizable<T> + VectorDifference<T> + VectorAdd<T> {
+    let images = target.images(symmetries);
+    solve_fixing_first_with(target, bag.merge_identical(), &images, when_solved)
+}
+
+fn solve_fixing_first_with<F, T>(target: &Target<T>, bag: Bag<T>, images: &[(CubeSymmetry, Translation<T>)], when_solved: &mut F) where F: (FnMut(Solution<T>)) + Sized, T: Clone + PartialOrd + Ord + Transformable + Normalizable<T> + VectorDifference<T> + VectorAdd<T> {
+    let (template, rest_of_bag) = match bag.split_first_counted() {
+        Some(split) => split,
+        None => return solve(target, bag, when_solved),
+    };
+    let placements = target.placements(&template);
+    let copies: BTreeSet<Vec<Position<T>>> = placements.iter().map(|piece| piece.iter().collect()).collect();
+    for piece in &placements {
+        let positions: Vec<Position<T>> = piece.iter().collect();
+        let smallest = images.iter().all(|(symmetry, translation)| {
+            positions <= piece.transformed(symmetry).translated(translation).iter().collect::<Vec<_>>()
+        });
+        if smallest {
+            solve_with(&target.place(piece), rest_of_bag.clone(), Solution::empty().record(piece), &mut |solution: Solution<T>|{
+                let first_copy = solution.pieces[1..].iter()
+                    .filter(|other| other.name() == piece.name())
+                    .map(|other| other.iter().collect::<Vec<_>>())
+                    .all(|other| !copies.contains(&other) || positions <= other);
+                if first_copy {
+                    when_solved(solution)
+                }
+            });
+        }
+    }
+    if let Some(bag) = bag.without_first_counted() {
+        solve_fixing_first_with(target, bag, images, when_solved)
+    }
+}
+
+impl<T> Target<T> where T: Clone + PartialOrd + Ord + Transformable + VectorDifference<T> + VectorAdd<T> {
+    /// The rotations that map the `Target` onto itself, up to a translation.
+    /// This is the twin of `Piece::symmetries`; a cube has all 24 rotations.
+    pub fn symmetries(&self) -> Vec<CubeSymmetry> {
+        if self.collection.is_empty() {
+            return CubeSymmetryIterator::rotations_only().collect();
+        }
+        self.images(CubeSymmetryIterator::rotations_only()).into_iter().map(|(symmetry, _)| symmetry).collect()
+    }
+
+    /// The `symmetries` that map the `Target` onto itself, each with the
+    /// `Translation` that brings the transformed `Target` back in place.
+    fn images<I>(&self, symmetries: I) -> Vec<(CubeSymmetry, Translation<T>)> where I: IntoIterator<Item=CubeSymmetry> {
+        match self.minimum_position() {
+            Some(minimum) => symmetries
+                .into_iter()
+                .filter_map(|symmetry| self.image(&symmetry, &minimum).map(|translation| (symmetry, translation)))
+                .collect(),
+            None => vec!(),
+        }
+    }
+
+    /// The `Translation` that, after applying `symmetry`, maps the `Target`
+    /// back onto itself. `None` when `symmetry` is not a symmetry of the
+    /// `Target`.
+    fn image(&self, symmetry: &CubeSymmetry, minimum: &Position<T>) -> Option<Translation<T>> {
+        let mut positions: Vec<Position<T>> = self.collection.to_vec();
+        for position in &mut positions {
+            position.transform(symmetry);
+        }
+        let translation = positions.iter().min()?.to(minimum);
+        for position in &mut positions {
+            position.translate(&translation);
+        }
+        positions.sort();
+        if positions == self.collection { Some(translation) } else { None }
+    }
+}
+
+/// Sorted cells of the `Piece`s of a `Solution` with their names.
+type LabeledKey<T> = Vec<(Vec<Position<T>>, Option<String>)>;
+
+impl<T> Solution<T> where T: Clone + PartialOrd + Ord {
+    /// Like `key`, with every `Piece` labeled with its name, so that packings
+    /// that only differ in where differently named `Piece`s go are distinct.
+    fn labeled_key<M>(&self, map: M) -> LabeledKey<T> where M: Fn(&mut Position<T>) {
+        let mut key: LabeledKey<T> = self.pieces
+            .iter()
+            .map(|piece| {
+                let mut positions: Vec<Position<T>> = piece.iter().collect();
+                positions.iter_mut().for_each(&map);
+                positions.sort();
+                (positions, piece.name().map(String::from))
+            })
+            .collect();
+        key.sort();
+        key
+    }
+
+    /// Sorted cells of the `Piece`s, after every `Position` is mapped, that
+    /// do not depend on the order in which the `Piece`s were placed.
+    fn key<M>(&self, map: M) -> Vec<Vec<Position<T>>> where M: Fn(&mut Position<T>) {
+        let mut key: Vec<Vec<Position<T>>> = self.pieces
+            .iter()
+            .map(|piece| {
+                let mut positions: Vec<Position<T>> = piece.iter().collect();
+                positions.iter_mut().for_each(&map);
+                positions.sort();
+                positions
+            })
+            .collect();
+        key.sort();
+        key
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Write;
+    use super::super::piece::{CubeSymmetryIterator, Position, Piece, Rgb, Template};
+    use super::super::pieces::{Bag, Bound};
+    use super::*;
+
+    /// A corner of the 2×2×2 cube: a cell with its neighbours along the axes.
+    fn tripod() -> Template<(i8, i8, i8)> {
+        Template::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, 0, 0),
+            Position::new(0, 1, 0),
+            Position::new(0, 0, 1),
+        ))
+    }
+
+    /// A `Target` with the `Bag` to pack it with.
+    type Puzzle = (Target<(i8, i8, i8)>, Bag<(i8, i8, i8)>);
+
+    /// Two tripods, which pack the 2×2×2 cube in 4 ways.
+    fn tripods_in_cube() -> Puzzle {
+        (Target::cuboid(2, 2, 2), Bag::new(vec!((2, tripod()))))
+    }
+
+    /// Four dominoes, which pack the 2×2×2 cube in 9 ways.
+    fn dominoes_in_cube() -> Puzzle {
+        let domino = Template::new(vec!(Position::new(0, 0, 0), Position::new(1, 0, 0)));
+        (Target::cuboid(2, 2, 2), Bag::new(vec!((4, domino))))
+    }
+
+    /// The sorted `Position`s of each `Piece` of a `Solution`, in order.
+    type ClassKey = Vec<Vec<Position<(i8, i8, i8)>>>;
+
+    /// The `Position`s of the `Piece`s of the image of `solution` under the
+    /// rotation that makes them smallest. `Solution`s that are rotations of
+    /// each other have the same key.
+    fn class_key(solution: &Solution<(i8, i8, i8)>) -> ClassKey {
+        CubeSymmetryIterator::rotations_only()
+            .map(|symmetry| {
+                let mut image = Solution { pieces: solution.pieces.to_vec(), symmetries: vec!() };
+                image.transform(&symmetry);
+                let minimum = image.covered_target().minimum_position().unwrap();
+                image.translate(&minimum.to(&Position::new(0, 0, 0)));
+                let mut key: ClassKey = image.pieces.iter().map(|piece| piece.iter().collect()).collect();
+                key.sort();
+                key
+            })
+            .min()
+            .unwrap()
+    }
+
+    #[test]
+    fn piece_should_fit_in_target() {
+        let target = Target::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, 0, 0),
+            Position::new(0, 1, 0),
+            Position::new(1, 1, 0),
+            Position::new(0, 0, 1),
+            Position::new(1, 0, 1),
+            Position::new(0, 1, 1),
+            Position::new(1, 1, 1),
+        ));
+
+        let piece = Piece::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, 0, 0),
+            Position::new(0, 1, 0),
+            Position::new(0, 0, 1),
+        ));
+
+        assert!(target.fits(&piece));
+    }
+
+    #[test]
+    fn target_should_contain_its_cells() {
+        let target = Target::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, 0, 0),
+        ));
+
+        assert!(target.contains(&Position::new(1, 0, 0)));
+        assert!(!target.contains(&Position::new(0, 1, 0)));
+    }
+
+    #[test]
+    fn cuboid_should_contain_all_positions_in_box() {
+        let target = Target::cuboid(1, 2, 2);
+
+        assert_eq!(target, Target::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(0, 0, 1),
+            Position::new(0, 1, 0),
+            Position::new(0, 1, 1),
+        )));
+    }
+
+    #[test]
+    fn box_minus_should_remove_holes_inside_box() {
+        let target = Target::box_minus((3, 3, 2), &[
+            Position::new(1, 1, 0),
+            Position::new(1, 1, 1),
+            Position::new(5, 0, 0),
+        ]);
+
+        assert_eq!(target.volume(), 3 * 3 * 2 - 2);
+        assert!(!target.contains(&Position::new(1, 1, 0)));
+        assert!(target.contains(&Position::new(0, 1, 0)));
+    }
+
+    #[test]
+    fn cuboid_should_report_its_dimensions() {
+        let target = Target::cuboid(3, 2, 4);
+        let shifted = Target::new(vec!(
+            Position::new(-1, 2, 5),
+            Position::new(1, 2, 5),
+        ));
+
+        assert_eq!(target.bounding_box(), Some((Position::new(0, 0, 0), Position::new(2, 1, 3))));
+        assert_eq!(target.dimensions(), (3, 2, 4));
+        assert_eq!(shifted.dimensions(), (3, 1, 1));
+        assert_eq!(Target::cuboid(0, 2, 2).bounding_box(), None);
+        assert_eq!(Target::cuboid(0, 2, 2).dimensions(), (0, 0, 0));
+    }
+
+    #[test]
+    fn cuboid_should_be_empty_for_degenerate_dimensions() {
+        assert!(Target::cuboid(0, 2, 2).is_packed());
+        assert!(Target::cuboid(2, -1, 2).is_packed());
+        assert!(Target::rectangle(-3, 2).is_packed());
+    }
+
+    #[test]
+    fn rectangle_should_contain_all_positions_in_rectangle() {
+        let target = Target::rectangle(2, 1);
+
+        assert_eq!(target, Target::new(vec!(
+            Position::d2(0, 0),
+            Position::d2(1, 0),
+        )));
+    }
+
+    #[test]
+    fn try_from_box_should_create_box() {
+        let target = Target::try_from_box(2, 1, 2).expect("to create box");
+
+        assert_eq!(target, Target::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(0, 0, 1),
+            Position::new(1, 0, 0),
+            Position::new(1, 0, 1),
+        )));
+        assert!(Target::try_from_box(128, 1, 1).is_ok());
+    }
+
+    #[test]
+    fn try_from_box_should_reject_overflowing_dimensions() {
+        assert_eq!(Target::try_from_box(1, 129, 1), Err(DimensionError::TooLarge(129)));
+    }
+
+    #[test]
+    fn from_grid_should_create_target() {
+        let grid = vec!(
+            vec!(
+                vec!(true, true),
+                vec!(false, true),
+            ),
+            vec!(
+                vec!(false, false),
+                vec!(true, false),
+            ),
+        );
+
+        let target = Target::from_grid(&grid).expect("to create target");
+
+        assert_eq!(target, Target::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, 0, 0),
+            Position::new(1, 1, 0),
+            Position::new(0, 1, 1),
+        )));
+    }
+
+    #[test]
+    fn from_grid_should_reject_inconsistent_grid() {
+        let grid = vec!(
+            vec!(
+                vec!(true, true),
+                vec!(true),
+            ),
+        );
+
+        assert_eq!(Target::from_grid(&grid), Err(DimensionError::Inconsistent));
+    }
+
+    #[test]
+    fn target_should_round_trip_through_csv() {
+        let input = "0,1,0\n0,0,0\n\n1,0,0\n0,0,1\n";
+
+        let target = Target::from_csv(input.as_bytes()).expect("to read csv");
+        let mut output: Vec<u8> = vec!();
+        target.to_csv(&mut output).expect("to write csv");
+
+        assert_eq!(String::from_utf8(output).unwrap(), String::from("0,0,0\n0,0,1\n0,1,0\n1,0,0\n"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn target_should_round_trip_through_json() {
+        let target = Target::cuboid(2, 1, 1);
+
+        let json = serde_json::to_string(&target).expect("to serialize");
+        let copy: Target<(i8, i8, i8)> = serde_json::from_str(&json).expect("to deserialize");
+
+        assert_eq!(json, String::from("{\"collection\":[[0,0,0],[1,0,0]]}"));
+        assert_eq!(copy, target);
+    }
+
+    #[test]
+    fn target_should_reject_malformed_csv() {
+        assert!(Target::from_csv("0,0\n".as_bytes()).is_err());
+        assert!(Target::from_csv("0,a,0\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn solve_should_pack_pieces() {
+        let (target, bag) = tripods_in_cube();
+
+        let mut solutions: Vec<Solution<(i8, i8, i8)>> = vec!();
+        solve(&target, bag.clone(), &mut |solution|{ solutions.push(solution)});
+        assert_eq!(solutions.len(), count_solutions(&target, bag));
+    }
+
+    #[test]
+    fn is_feasible_should_compare_volumes() {
+        let target = Target::cuboid(2, 2, 2);
+        let tripod = tripod();
+
+        assert_eq!(target.volume(), 8);
+        assert!(is_feasible(&target, &Bag::new(vec!((2, tripod.clone())))));
+        assert!(!is_feasible(&target, &Bag::new(vec!((3, tripod)))));
+    }
+
+    #[test]
+    fn count_solutions_should_count_packings() {
+        let (target, bag) = tripods_in_cube();
+
+        assert_eq!(count_solutions(&target, bag), 4);
+
+        let (target, bag) = dominoes_in_cube();
+
+        assert_eq!(count_solutions(&target, bag), 9);
+    }
+
+    #[test]
+    fn symmetry_breaking_should_report_every_class_of_solutions() {
+        for (target, bag) in [tripods_in_cube(), dominoes_in_cube()] {
+            let mut classes: BTreeSet<ClassKey> = BTreeSet::new();
+            solve(&target, bag.clone(), &mut |solution|{ classes.insert(class_key(&solution)); });
+            let mut canonical: Vec<ClassKey> = vec!();
+            solve_canonical(&target, bag.clone(), CubeSymmetryIterator::rotations_only(), &mut |solution| canonical.push(class_key(&solution)));
+            let mut unique: BTreeSet<ClassKey> = BTreeSet::new();
+            solve_unique(&target, bag.clone(), CubeSymmetryIterator::rotations_only(), &mut |solution|{ unique.insert(class_key(&solution)); });
+            let mut fixing_first: BTreeSet<ClassKey> = BTreeSet::new();
+            solve_fixing_first(&target, bag.clone(), CubeSymmetryIterator::rotations_only(), &mut |solution|{ fixing_first.insert(class_key(&solution)); });
+
+            canonical.sort();
+            assert!(classes.len() < count_solutions(&target, bag));
+            assert_eq!(canonical, classes.iter().cloned().collect::<Vec<_>>(), "every class once");
+            assert_eq!(unique, classes);
+            assert_eq!(fixing_first, classes);
+        }
+    }
+
+    #[test]
+    fn solve_unique_should_report_one_solution_per_symmetry_class() {
+        let (target, bag) = tripods_in_cube();
+
+        let mut unique: usize = 0;
+        solve_unique(&target, bag.clone(), CubeSymmetryIterator::rotations_only(), &mut |_|{ unique += 1 });
+        let mut all: usize = 0;
+        solve_unique(&target, bag.clone(), vec!(CubeSymmetry::E0123), &mut |_|{ all += 1 });
+
+        assert_eq!(unique, 1);
+        assert_eq!(all, count_solutions(&target, bag));
+    }
+
+    #[test]
+    fn symmetries_should_detect_symmetry_group_of_target() {
+        let ell = Target::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, 0, 0),
+            Position::new(0, 1, 0),
+        ));
+
+        assert_eq!(Target::cuboid(2, 2, 2).symmetries().len(), 24);
+        assert_eq!(Target::cuboid(3, 2, 1).symmetries().len(), 4);
+        assert_eq!(ell.symmetries().len(), 2);
+        assert!(ell.symmetries().contains(&CubeSymmetry::E0123));
+    }
+
+    #[test]
+    fn solve_fixing_first_should_skip_symmetric_first_placements() {
+        let (target, bag) = tripods_in_cube();
+
+        let mut fixed: usize = 0;
+        solve_fixing_first(&target, bag.clone(), CubeSymmetryIterator::rotations_only(), &mut |solution|{
+            assert_eq!(solution.pieces.len(), 2);
+            fixed += 1
+        });
+        let mut all: usize = 0;
+        solve_fixing_first(&target, bag.clone(), vec!(CubeSymmetry::E0123), &mut |_|{ all += 1 });
+
+        assert_eq!(fixed, 1);
+        assert_eq!(all, count_solutions(&target, bag), "a packing is reported once, whichever copy is fixed");
+    }
+
+    #[test]
+    fn solve_fixing_first_should_also_search_without_an_optional_template() {
+        let target = Target::cuboid(2, 2, 2);
+        let cube = Template::new(target.collection.clone());
+        let tripod = tripod();
+        let bag = Bag::new(vec!((1, cube), (2, tripod)));
+
+        let mut fixed: usize = 0;
+        solve_fixing_first(&target, bag.clone(), CubeSymmetryIterator::rotations_only(), &mut |_|{ fixed += 1 });
+        let mut all: usize = 0;
+        solve_fixing_first(&target, bag.clone(), vec!(CubeSymmetry::E0123), &mut |_|{ all += 1 });
+
+        assert_eq!(fixed, 2, "the cube on its own and one packing with tripods");
+        assert_eq!(all, count_solutions(&target, bag));
+    }
+
+    #[test]
+    fn cell_map_should_cover_every_cell_once() {
+        let target = Target::cuboid(2, 2, 2);
+        let bag = Bag::new(vec!(
+            (1,tripod().with_name("tripod")),
+            (1,tripod()),
+        ));
+        let solution = first_solution(&target, bag).expect("a packing");
+
+        let cell_map = solution.cell_map();
+        let labeled_cell_map = solution.labeled_cell_map();
+
+        assert_eq!(cell_map.len(), target.volume());
+        assert!(target.positions().iter().all(|position| cell_map.contains_key(position)));
+        assert_eq!(cell_map.values().filter(|index| **index == 0).count(), 4);
+        assert_eq!(labeled_cell_map.len(), target.volume());
+        let mut labels: Vec<&String> = labeled_cell_map.values().collect();
+        labels.sort();
+        labels.dedup();
+        assert_eq!(labels.len(), 2);
+        assert!(labels.iter().any(|label| *label == "tripod"));
+    }
+
+    #[test]
+    fn cell_maps_should_tell_copies_apart_and_report_overlaps() {
+        let domino = |x: i8| Piece::named(vec!(Position::new(x, 0, 0), Position::new(x + 1, 0, 0)), "1");
+        let solution = Solution::empty().record(&domino(0)).record(&domino(2));
+
+        let cell_map = solution.try_cell_map().expect("no overlap");
+
+        assert_eq!(cell_map, solution.cell_map());
+        assert_eq!(cell_map[&Position::new(1, 0, 0)], 0);
+        assert_eq!(cell_map[&Position::new(2, 0, 0)], 1);
+        assert_eq!(solution.labeled_cell_map()[&Position::new(2, 0, 0)], "1");
+
+        let overlapping = solution.record(&domino(1));
+        assert_eq!(overlapping.try_cell_map(), Err(Overlap { first: 0, second: 2 }));
+    }
+
+    #[test]
+    fn transformed_solution_should_pack_the_same_target() {
+        let (target, bag) = tripods_in_cube();
+        let solution = first_solution(&target, bag).expect("a packing");
+
+        for symmetry in CubeSymmetryIterator::with_reflections() {
+            let mut image = Solution { pieces: solution.pieces.to_vec(), symmetries: vec!() };
+            image.transform(&symmetry);
+            let minimum = image.covered_target().minimum_position().unwrap();
+            image.translate(&minimum.to(&Position::new(0, 0, 0)));
+
+            assert_eq!(image.covered_target(), target);
+            assert!(image.pieces.iter().all(|piece| target.fits(piece)));
+            assert!(!image.has_overlap());
+        }
+    }
+
+    #[test]
+    fn solve_unique_should_ignore_symmetries_of_the_cube_that_are_not_symmetries_of_the_target() {
+        let target = Target::cuboid(3, 1, 1);
+        let bag = Bag::new(vec!(
+            (1,Template::new(vec!(
+                Position::new(0, 0, 0),
+            ))),
+            (1,Template::new(vec!(
+                Position::new(0, 0, 0),
+                Position::new(1, 0, 0),
+            ))),
+        ));
+
+        let mut count: usize = 0;
+        solve_unique(&target, bag, CubeSymmetryIterator::rotations_only(), &mut |_|{ count += 1 });
+
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn try_solve_should_reject_empty_target_and_pieces() {
+        let (target, bag) = tripods_in_cube();
+        let empty: Template<(i8, i8, i8)> = Template::new(vec!());
+
+        let mut count: usize = 0;
+        let result = try_solve(&target, bag.clone(), &mut |_|{ count += 1 });
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(count, count_solutions(&target, bag.clone()));
+        assert_eq!(try_solve(&Target::cuboid(0, 0, 0), bag, &mut |_|{}), Err(SolverError::EmptyTarget));
+        assert_eq!(try_solve(&target, Bag::new(vec!((2, tripod()), (1, empty))), &mut |_|{}), Err(SolverError::EmptyPiece));
+    }
+
+    #[test]
+    fn solve_with_stats_should_report_search_statistics() {
+        let (target, bag) = tripods_in_cube();
+
+        let mut count: usize = 0;
+        let stats = solve_with_stats(&target, bag.clone(), &mut |_|{ count += 1 });
+
+        assert_eq!(count, count_solutions(&target, bag));
+        assert_eq!(stats, SolveStats { nodes_visited: 9, placements_tried: 40, solutions_found: 4, max_depth: 2 });
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn solve_parallel_should_find_all_packings() {
+        let target = Target::cuboid(3, 3, 3);
+        let bag = Bag::new(vec!(
+            (6,Template::new(vec!(
+                Position::new(0, 0, 0),
+                Position::new(1, 0, 0),
+                Position::new(0, 1, 0),
+                Position::new(1, 1, 0),
+            ))),
+            (3,Template::new(vec!(
+                Position::new(0, 0, 0),
+            ))),
+        ));
+
+        let mut expected: Vec<String> = vec!();
+        solve(&target, bag.clone(), &mut |solution| expected.push(format!("{}", solution)));
+        let mut actual: Vec<String> = vec!();
+        solve_parallel(&target, bag, &mut |solution| actual.push(format!("{}", solution)));
+        expected.sort();
+        actual.sort();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn solve_should_support_wide_coordinates() {
+        let mut cells: Vec<Position<(i16, i16, i16)>> = vec!();
+        for x in 0..2 {
+            for y in 0..2 {
+                for z in 0..2 {
+                    cells.push(Position::from((1000 + x, -1000 + y, 200 + z)));
+                }
+            }
+        }
+        let target = Target::new(cells);
+        let bag = Bag::new(vec!(
+            (2,Template::new(vec!(
+                Position::from((0, 0, 0)),
+                Position::from((1, 0, 0)),
+                Position::from((0, 1, 0)),
+                Position::from((0, 0, 1)),
+            ))),
+        ));
+
+        let mut solutions: Vec<String> = vec!();
+        solve(&target, bag, &mut |solution| solutions.push(format!("{}", solution)));
+
+        assert_eq!(solutions.len(), 4);
+        assert!(solutions[0].starts_with("<[(1000, -1000, 200)"));
+    }
+
+    #[test]
+    fn most_constrained_strategy_should_visit_fewer_nodes() {
+        let pentomino = |cells: &[(i8, i8)]| Template::new(cells.iter().map(|&(x, y)| Position::new(x, y, 0)).collect());
+        let target = Target::cuboid(5, 4, 1);
+        let bag = Bag::new(vec!(
+            (1, pentomino(&[(0, 0), (1, 0), (2, 0), (3, 0), (0, 1)])),
+            (1, pentomino(&[(0, 0), (1, 0), (2, 0), (0, 1), (1, 1)])),
+            (1, pentomino(&[(0, 0), (1, 0), (2, 0), (0, 1), (2, 1)])),
+            (1, pentomino(&[(0, 0), (1, 0), (2, 0), (2, 1), (3, 1)])),
+        ));
+
+        let mut lex_min: usize = 0;
+        let lex_min_stats = solve_with_strategy(&target, bag.clone(), SolveStrategy::LexMin, &mut |_|{ lex_min += 1 });
+        let mut most_constrained: usize = 0;
+        let most_constrained_stats = solve_with_strategy(&target, bag, SolveStrategy::MostConstrained, &mut |_|{ most_constrained += 1 });
+
+        assert_eq!(lex_min, 8);
+        assert_eq!(most_constrained, lex_min);
+        assert!(most_constrained_stats.nodes_visited < lex_min_stats.nodes_visited);
+    }
+
+    #[test]
+    fn solve_hardest_first_should_visit_fewer_nodes() {
+        let pentomino = |cells: &[(i8, i8)]| Template::new(cells.iter().map(|&(x, y)| Position::new(x, y, 0)).collect());
+        let target = Target::cuboid(5, 4, 1);
+        let bag = Bag::new(vec!(
+            (1, pentomino(&[(0, 0), (1, 0), (2, 0), (2, 1), (3, 1)])),
+            (1, pentomino(&[(0, 0), (1, 0), (2, 0), (0, 1), (2, 1)])),
+            (1, pentomino(&[(0, 0), (1, 0), (2, 0), (0, 1), (1, 1)])),
+            (1, pentomino(&[(0, 0), (1, 0), (2, 0), (3, 0), (4, 0)])),
+        ));
+
+        let mut in_order: usize = 0;
+        let mut in_order_stats = SolveStats::default();
+        solve_ordered_with(&target, bag.clone(), Solution::empty(), &mut in_order_stats, &mut |_|{ in_order += 1 });
+        let mut hardest_first: usize = 0;
+        let hardest_first_stats = solve_hardest_first(&target, bag, &mut |_|{ hardest_first += 1 });
+
+        assert_eq!(in_order, 8);
+        assert_eq!(hardest_first, in_order);
+        assert!(hardest_first_stats.nodes_visited < in_order_stats.nodes_visited);
+    }
+
+    #[test]
+    fn target_should_translate() {
+        let mut target = Target::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, 0, 0),
+            Position::new(1, 1, 0),
+            Position::new(1, 1, 1),
+        ));
+        let translation = Translation::new(5, -3, 0);
+
+        target.translate(&translation);
+
+        assert_eq!(target.positions(), &[
+            Position::new(5, -3, 0),
+            Position::new(6, -3, 0),
+            Position::new(6, -2, 0),
+            Position::new(6, -2, 1),
+        ]);
+    }
+
+    #[test]
+    fn target_should_transform() {
+        let mut target = Target::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, 0, 0),
+            Position::new(1, 1, 0),
+            Position::new(1, 1, 1),
+        ));
+
+        target.transform(&CubeSymmetry::E2103);
+
+        assert_eq!(target.positions(), &[
+            Position::new(0, 0, 0),
+            Position::new(0, 1, 0),
+            Position::new(1, 1, -1),
+            Position::new(1, 1, 0),
+        ]);
+    }
+
+    #[test]
+    fn targets_with_the_same_cells_should_hash_equal() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::Hasher;
+
+        let hash = |target: &Target<(i8, i8, i8)>| {
+            let mut hasher = DefaultHasher::new();
+            target.hash(&mut hasher);
+            hasher.finish()
+        };
+        let forward = Target::new(vec!(Position::new(0, 0, 0), Position::new(1, 0, 0), Position::new(0, 1, 0)));
+        let backward = Target::new(vec!(Position::new(0, 1, 0), Position::new(1, 0, 0), Position::new(0, 0, 0)));
+
+        assert_eq!(forward, backward);
+        assert_eq!(hash(&forward), hash(&backward));
+        assert_ne!(forward, Target::new(vec!(Position::new(0, 0, 0), Position::new(1, 0, 0))));
+    }
+
+    #[test]
+    fn contains_piece_should_check_every_position() {
+        let target = Target::cuboid(2, 1, 1);
+
+        assert!(target.contains_piece(&Piece::new(vec!(Position::new(0, 0, 0), Position::new(1, 0, 0)))));
+        assert!(!target.contains_piece(&Piece::new(vec!(Position::new(1, 0, 0), Position::new(2, 0, 0)))));
+    }
+
+    #[test]
+    fn collecting_positions_should_create_target() {
+        let positions = vec!(
+            Position::new(1, 0, 0),
+            Position::new(0, 0, 0),
+        );
+
+        assert_eq!(positions.clone().into_iter().collect::<Target<_>>(), Target::new(positions));
+    }
+
+    #[test]
+    fn connected_components_should_split_target() {
+        let target = Target::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, 0, 0),
+            Position::new(1, 1, 0),
+            Position::new(3, 0, 0),
+            Position::new(1, 1, 1),
+        ));
+
+        let components = target.connected_components();
+
+        assert_eq!(components, vec!(
+            Target::new(vec!(
+                Position::new(0, 0, 0),
+                Position::new(1, 0, 0),
+                Position::new(1, 1, 0),
+                Position::new(1, 1, 1),
+            )),
+            Target::new(vec!(
+                Position::new(3, 0, 0),
+            )),
+        ));
+        let edge = Target::try_from_box(128, 1, 1).expect("fits in i8");
+        assert_eq!(edge.connected_components().len(), 1);
+        assert!(edge.is_connected());
+    }
+
+    #[test]
+    fn to_layers_should_draw_every_z_layer() {
+        let target = Target::new(vec!(
+            Position::new(0, 0, 1),
+            Position::new(1, 0, 1),
+            Position::new(0, 1, 1),
+            Position::new(1, 1, 3),
+        ));
+
+        let layers = target.to_layers();
+
+        assert_eq!(layers.lines().filter(|line| line.starts_with("z = ")).count(), 3);
+        assert_eq!(layers, "z = 1\n##\n#.\nz = 2\n..\n..\nz = 3\n..\n.#\n");
+        assert_eq!(Target::new(vec!()).to_layers(), "");
+    }
+
+    #[test]
+    fn is_connected_should_detect_separate_blobs() {
+        let cube = |x: i8| (0..2).flat_map(move |dx| (0..2).flat_map(move |y| (0..2).map(move |z| Position::new(x + dx, y, z))));
+
+        assert!(Target::new(cube(0).collect()).is_connected());
+        assert!(Target::new(cube(0).chain(cube(2)).collect()).is_connected());
+        assert!(!Target::new(cube(0).chain(cube(3)).collect()).is_connected());
+        assert!(Target::new(vec!()).is_connected());
+    }
+
+    #[test]
+    fn prune_cavities_should_stop_search_around_stranded_cell() {
+        let mut cells: Vec<Position<(i8, i8, i8)>> = vec!();
+        for x in 0..4 {
+            for y in 0..2 {
+                cells.push(Position::new(x, y, 0));
+            }
+        }
+        cells.push(Position::new(6, 0, 0));
+        let target = Target::new(cells);
+        let bag = Bag::new(vec!(
+            (3,Template::new(vec!(
+                Position::new(0, 0, 0),
+                Position::new(1, 0, 0),
+                Position::new(2, 0, 0),
+            ))),
+        ));
+
+        let mut plain: usize = 0;
+        let plain_stats = solve_with_pruning(&target, bag.clone(), |_, _| false, &mut |_|{ plain += 1 });
+        let mut pruned: usize = 0;
+        let pruned_stats = solve_with_pruning(&target, bag, prune_cavities, &mut |_|{ pruned += 1 });
+
+        assert_eq!(plain, 0);
+        assert_eq!(pruned, 0);
+        assert_eq!(pruned_stats.nodes_visited, 1);
+        assert!(plain_stats.nodes_visited > pruned_stats.nodes_visited);
+    }
+
+    #[test]
+    fn validate_should_report_why_bag_can_not_pack_target() {
+        let tetromino = |row: i8| Template::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, 0, 0),
+            Position::new(2, 0, 0),
+            Position::new(row, 1, 0),
+        ));
+        let target = Target::cuboid(4, 2, 1);
+
+        assert_eq!(validate(&target, &Bag::exactly(vec!((1,tetromino(0))))), Err(ValidationError::VolumeMismatch { target: 8, required: 4, available: 4 }));
+        assert_eq!(validate(&target, &Bag::exactly(vec!((3,tetromino(0))))), Err(ValidationError::VolumeMismatch { target: 8, required: 12, available: 12 }));
+        assert_eq!(validate(&target, &Bag::new_bounded(vec!((Bound::UpTo(3),tetromino(0))))), Ok(()));
+        assert_eq!(validate(&target, &Bag::new(vec!((1,tetromino(0)),(1,tetromino(1))))), Err(ValidationError::ColorImbalance));
+        assert_eq!(validate(&target, &Bag::new(vec!((2,tetromino(0))))), Ok(()));
+        assert_eq!(validate(&Target::cuboid(0, 0, 0), &Bag::new(vec!((2,tetromino(0))))), Err(ValidationError::EmptyTarget));
+        assert_eq!(validate(&target, &Bag::new(vec!((2,Template::new(vec!()))))), Err(ValidationError::EmptyPiece));
+    }
+
+    #[test]
+    fn prune_parity_should_reject_unbalanced_packing() {
+        let target = Target::cuboid(4, 2, 1);
+        let bag = Bag::new(vec!(
+            (1,Template::new(vec!(
+                Position::new(0, 0, 0),
+                Position::new(1, 0, 0),
+                Position::new(2, 0, 0),
+                Position::new(1, 1, 0),
+            ))),
+            (1,Template::new(vec!(
+                Position::new(0, 0, 0),
+                Position::new(1, 0, 0),
+                Position::new(2, 0, 0),
+                Position::new(3, 0, 0),
+            ))),
+        ));
+
+        let mut plain: usize = 0;
+        let plain_stats = solve_with_pruning(&target, bag.clone(), |_, _| false, &mut |_|{ plain += 1 });
+        let mut pruned: usize = 0;
+        let pruned_stats = solve_with_pruning(&target, bag, prune_parity, &mut |_|{ pruned += 1 });
+
+        assert_eq!(target.color_balance(), 0);
+        assert_eq!(plain, 0);
+        assert_eq!(pruned, 0);
+        assert_eq!(pruned_stats.nodes_visited, 1);
+        assert!(plain_stats.nodes_visited > pruned_stats.nodes_visited);
+    }
+
+    #[test]
+    fn named_pieces_should_carry_template_names() {
+        let target = Target::cuboid(3, 1, 1);
+        let bag = Bag::new(vec!(
+            (1,Template::new(vec!(
+                Position::new(0, 0, 0),
+                Position::new(1, 0, 0),
+            )).with_name("domino")),
+            (1,Template::new(vec!(
+                Position::new(0, 0, 0),
+            )).with_name("monomino")),
+        ));
+
+        let solution = first_solution(&target, bag).expect("a packing");
+        let names: Vec<(Option<&str>, usize)> = solution.named_pieces().into_iter().map(|(name, piece)| (name, piece.len())).collect();
+
+        assert_eq!(names, vec!((Some("domino"), 2), (Some("monomino"), 1)));
+    }
+
+    #[test]
+    fn solution_should_cover_target() {
+        let (target, bag) = tripods_in_cube();
+
+        let solution = first_solution(&target, bag).expect("a packing");
+
+        assert_eq!(solution.covered_target(), target);
+        assert!(!solution.has_overlap());
+    }
+
+    #[test]
+    fn remaining_should_be_open_part_of_target() {
+        let (target, bag) = tripods_in_cube();
+
+        let solution = first_solution(&target, bag).expect("a packing");
+        let first_piece = Solution::empty().record(&solution.pieces[0]);
+
+        assert_eq!(Solution::empty().remaining(&target), target);
+        assert_eq!(first_piece.remaining(&target).volume(), 4);
+        assert!(solution.remaining(&target).is_packed());
+    }
+
+    #[test]
+    fn orientation_stats_should_account_for_every_placed_piece() {
+        let target = Target::cuboid(3, 3, 3);
+        let slab = Template::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, 0, 0),
+            Position::new(0, 1, 0),
+            Position::new(1, 1, 0),
+        ));
+        let bag = Bag::new(vec!(
+            (6,slab.clone()),
+            (3,Template::new(vec!(
+                Position::new(0, 0, 0),
+            ))),
+        ));
+
+        let mut count: usize = 0;
+        let stats = solve_with_orientation_stats(&target, bag, &mut |_|{ count += 1 });
+        let tally = stats.tally;
+        let slab_orientations: Vec<Piece<(i8, i8, i8)>> = slab.into_iter().collect();
+
+        assert_eq!(count, 8);
+        assert_eq!(stats.unmatched, 0);
+        assert_eq!(tally.values().sum::<usize>(), count * 9);
+        assert_eq!(tally.iter().filter(|((index, _), _)| *index == 0).map(|(_, used)| used).sum::<usize>(), count * 6);
+        assert_eq!(tally.len(), slab_orientations.len() + 1);
+        assert!(tally.keys().filter(|(index, _)| *index == 0).all(|(_, orientation)| slab_orientations.contains(orientation)));
+    }
+
+    #[test]
+    fn orientation_stats_should_tell_apart_templates_with_the_same_shape() {
+        let target = Target::cuboid(4, 1, 1);
+        let bag = Bag::exactly(vec!(
+            (1,Template::new(vec!(Position::new(0, 0, 0), Position::new(1, 0, 0)))),
+            (1,Template::new(vec!(Position::new(0, 0, 0), Position::new(0, 1, 0)))),
+        ));
+        let along_x = Piece::new(vec!(Position::new(0, 0, 0), Position::new(1, 0, 0)));
+
+        let mut count: usize = 0;
+        let stats = solve_with_orientation_stats(&target, bag, &mut |_|{ count += 1 });
+
+        assert_eq!(count, 2);
+        assert_eq!(stats.unmatched, 0);
+        assert_eq!(stats.tally.len(), 2);
+        assert_eq!(stats.tally[&(0, along_x.clone())], 2);
+        assert_eq!(stats.tally[&(1, along_x)], 2);
+    }
+
+    #[test]
+    fn recorded_symmetry_should_orient_template_like_placed_piece() {
+        let target = Target::cuboid(2, 2, 2);
+        let tripod = tripod();
+        let bag = Bag::new(vec!((2,tripod.clone())));
+
+        let solution = first_solution(&target, bag).expect("a packing");
+
+        assert_eq!(solution.symmetries().len(), 2);
+        for (piece, symmetry) in solution.pieces.iter().zip(solution.symmetries()) {
+            let symmetry = symmetry.expect("a recorded symmetry");
+            assert_eq!(Piece::from(tripod.clone()).transformed(&symmetry).normalized(), piece.normalized());
+        }
+        assert_eq!(Solution::empty().record(&solution.pieces[0]).symmetries(), &[None]);
+    }
+
+    #[test]
+    fn solution_should_iterate_over_its_pieces() {
+        let domino = Piece::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, 0, 0),
+        ));
+        let cell = Piece::new(vec!(
+            Position::new(0, 1, 0),
+        ));
+        let solution = Solution::empty().record(&domino).record(&cell);
+
+        assert_eq!(solution.iter().count(), 2);
+        assert_eq!(solution.iter().collect::<Vec<_>>(), vec!(&domino, &cell));
+        assert_eq!(solution.pieces(), &[domino, cell]);
+    }
+
+    #[test]
+    fn solution_should_count_its_pieces() {
+        let solution = Solution::empty().record(&Piece::new(vec!(
+            Position::new(0, 0, 0),
+        )));
+
+        assert!(Solution::<(i8, i8, i8)>::empty().is_empty());
+        assert!(!solution.is_empty());
+        assert_eq!(solution.len(), 1);
+    }
+
+    #[test]
+    fn solutions_should_never_overlap() {
+        let target = Target::cuboid(3, 3, 3);
+        let bag = Bag::new(vec!(
+            (6,Template::new(vec!(
+                Position::new(0, 0, 0),
+                Position::new(1, 0, 0),
+                Position::new(0, 1, 0),
+                Position::new(1, 1, 0),
+            ))),
+            (3,Template::new(vec!(
+                Position::new(0, 0, 0),
+            ))),
+        ));
+
+        let mut count: usize = 0;
+        solve(&target, bag, &mut |solution|{
+            assert!(!solution.has_overlap());
+            assert_eq!(solution.covered_target(), target);
+            count += 1;
+        });
+
+        assert!(count > 0);
+    }
+
+    #[test]
+    fn solution_should_detect_overlap() {
+        let solution =
+            Solution::empty()
+            .record(&Piece::new(vec!(Position::new(0, 0, 0), Position::new(1, 0, 0))))
+            .record(&Piece::new(vec!(Position::new(1, 0, 0), Position::new(2, 0, 0))));
+
+        assert!(solution.has_overlap());
+        assert_eq!(solution.covered_target(), Target::cuboid(3, 1, 1));
+    }
+
+    #[test]
+    fn solve_at_most_should_stop_at_limit() {
+        let (target, bag) = tripods_in_cube();
+
+        let mut calls: usize = 0;
+        let delivered = solve_at_most(&target, bag.clone(), 2, &mut |_|{ calls += 1 });
+        assert_eq!(calls, 2);
+        assert_eq!(delivered, 2);
+
+        assert_eq!(solve_at_most(&target, bag.clone(), 10, &mut |_|{}), 4);
+        assert_eq!(solve_at_most(&target, bag, 0, &mut |_|{ panic!("no solution requested") }), 0);
+    }
+
+    #[test]
+    fn solutions_should_yield_what_solve_finds() {
+        let target = Target::cuboid(3, 3, 3);
+        let bag = Bag::new(vec!(
+            (6,Template::new(vec!(
+                Position::new(0, 0, 0),
+                Position::new(1, 0, 0),
+                Position::new(0, 1, 0),
+                Position::new(1, 1, 0),
+            ))),
+            (3,Template::new(vec!(
+                Position::new(0, 0, 0),
+            ))),
+        ));
+
+        let mut expected: Vec<String> = vec!();
+        solve(&target, bag.clone(), &mut |solution| expected.push(format!("{}", solution)));
+        let actual: Vec<String> = solutions(&target, bag.clone()).map(|solution| format!("{}", solution)).collect();
+
+        assert_eq!(actual, expected);
+        assert_eq!(solutions(&target, bag).take(1).count(), 1);
+        assert_eq!(solutions(&Target::new(vec!()), Bag::<(i8, i8, i8)>::new(vec!())).count(), 1);
+    }
+
+    #[test]
+    fn has_solution_should_tell_if_bag_packs_target() {
+        let target = Target::cuboid(2, 2, 2);
+        let tripods = Bag::new(vec!((2, tripod())));
+        let rods = Bag::new(vec!(
+            (3,Template::new(vec!(
+                Position::new(0, 0, 0),
+                Position::new(1, 0, 0),
+                Position::new(2, 0, 0),
+            ))),
+        ));
+
+        assert!(has_solution(&target, tripods));
+        assert!(!has_solution(&target, rods));
+    }
+
+    #[test]
+    fn solve_should_leave_optional_pieces_unused() {
+        let target = Target::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, 0, 0),
+            Position::new(2, 0, 0),
+        ));
+        let domino = Template::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, 0, 0),
+        ));
+        let monomino = Template::new(vec!(
+            Position::new(0, 0, 0),
+        ));
+        let sizes = |bag: Bag<(i8, i8, i8)>| {
+            let mut sizes: Vec<Vec<usize>> = vec!();
+            solve(&target, bag, &mut |solution| sizes.push(solution.pieces.iter().map(Piece::len).collect()));
+            sizes.sort();
+            sizes
+        };
+
+        assert_eq!(
+            sizes(Bag::new_bounded(vec!((Bound::UpTo(1), domino.clone()), (Bound::UpTo(3), monomino.clone())))),
+            vec!(vec!(1, 1, 1), vec!(1, 2), vec!(2, 1)));
+        assert_eq!(
+            sizes(Bag::new_bounded(vec!((Bound::Exactly(1), domino.clone()), (Bound::UpTo(3), monomino.clone())))),
+            vec!(vec!(1, 2), vec!(2, 1)));
+        assert_eq!(count_solutions(&target, Bag::exactly(vec!((1, domino), (2, monomino)))), 0);
+    }
+
+    #[test]
+    fn solve_budgeted_should_report_incomplete_search() {
+        let (target, bag) = tripods_in_cube();
+
+        let (status, solutions) = solve_budgeted(&target, bag.clone(), 3);
+        assert_eq!(status, SolveStatus::Incomplete);
+        assert_eq!(solutions.len(), 1);
+
+        let (status, solutions) = solve_budgeted(&target, bag.clone(), 9);
+        assert_eq!(status, SolveStatus::Complete);
+        assert_eq!(solutions.len(), count_solutions(&target, bag));
+    }
+
+    #[test]
+    fn solve_channel_should_send_every_solution() {
+        let (target, bag) = tripods_in_cube();
+        let expected = count_solutions(&target, bag.clone());
+        let (sender, receiver) = std::sync::mpsc::channel();
+
+        let solver = std::thread::spawn(move || solve_channel(&target, bag, sender));
+        let solutions: Vec<Solution<(i8, i8, i8)>> = receiver.iter().collect();
+        solver.join().unwrap();
+
+        assert_eq!(solutions.len(), expected);
+    }
+
+    #[test]
+    fn scan_order_should_change_search_tree_but_not_solutions() {
+        let target = Target::cuboid(4, 2, 2);
+        let bag = Bag::new(vec!(
+            (4,Template::new(vec!(
+                Position::new(0, 0, 0),
+                Position::new(1, 0, 0),
+                Position::new(2, 0, 0),
+                Position::new(0, 1, 0),
+            ))),
+        ));
+
+        let zyx_options = SolveOptions { scan_order: ScanOrder::Zyx, ..SolveOptions::default() };
+        let mut by_xyz = vec!();
+        let xyz = solve_with_options(&target, bag.clone(), SolveOptions::default(), &mut |solution| by_xyz.push(solution.key(|_| {})));
+        let mut by_zyx = vec!();
+        let zyx = solve_with_options(&target, bag.clone(), zyx_options, &mut |solution| by_zyx.push(solution.key(|_| {})));
+
+        by_xyz.sort();
+        by_zyx.sort();
+        assert_eq!(by_xyz.len(), count_solutions(&target, bag.clone()));
+        assert_eq!(by_zyx, by_xyz);
+        assert_eq!(xyz, solve_with_stats(&target, bag, &mut |_|{}));
+        assert_ne!(zyx.nodes_visited, xyz.nodes_visited);
+    }
+
+    #[test]
+    fn solve_canonical_should_identify_interchanged_copies_and_symmetric_images() {
+        let target = Target::cuboid(2, 2, 2);
+        let tripod = tripod();
+        let bag = Bag::new(vec!((1,tripod.clone()), (1,tripod.clone())));
+
+        let mut unique: usize = 0;
+        solve_unique(&target, bag.clone(), CubeSymmetryIterator::rotations_only(), &mut |_|{ unique += 1 });
+        let mut canonical: usize = 0;
+        solve_canonical(&target, bag.clone(), CubeSymmetryIterator::rotations_only(), &mut |_|{ canonical += 1 });
+
+        assert_eq!(count_solutions(&target, bag), 8);
+        assert_eq!(unique, 2);
+        assert_eq!(canonical, 1);
+    }
+
+    #[test]
+    fn solve_should_pack_a_board_with_dominoes() {
+        let target = Target::rectangle(2, 2);
+        let domino = Template::new(vec!(
+            Position::d2(0, 0),
+            Position::d2(1, 0),
+        ));
+        let bag = Bag::new(vec!((2,domino.clone())));
+
+        let mut solutions: Vec<String> = vec!();
+        solve(&target, bag, &mut |solution| solutions.push(solution.to_grid()));
+        solutions.sort();
+
+        assert_eq!(solutions, vec!(String::from("AA\nBB\n"), String::from("AB\nAB\n")));
+        assert_eq!(count_solutions(&Target::rectangle(1, 1), Bag::new_bounded(vec!((Bound::UpTo(1),domino)))), 0);
+    }
+
+    #[test]
+    fn all_solutions_should_collect_every_packing() {
+        let (target, bag) = tripods_in_cube();
+
+        let mut expected: Vec<String> = vec!();
+        solve(&target, bag.clone(), &mut |solution| expected.push(format!("{}", solution)));
+        let solutions = all_solutions(&target, bag.clone());
+
+        assert_eq!(solutions.len(), count_solutions(&target, bag));
+        assert_eq!(solutions.iter().map(|solution| format!("{}", solution)).collect::<Vec<String>>(), expected);
+    }
+
+    #[test]
+    fn first_solution_should_find_a_packing() {
+        let (target, bag) = tripods_in_cube();
+
+        let solution = first_solution(&target, bag).expect("a packing");
+
+        assert_eq!(solution.pieces.len(), 2);
+    }
+
+    #[test]
+    fn first_solution_should_be_none_without_packing() {
+        let target = Target::cuboid(2, 2, 2);
+        let bag = Bag::new(vec!(
+            (3,Template::new(vec!(
+                Position::new(0, 0, 0),
+                Position::new(1, 0, 0),
+                Position::new(2, 0, 0),
+            ))),
+        ));
+
+        assert!(first_solution(&target, bag).is_none());
+    }
+
+    #[test]
+    fn solve_cancellable_should_stop_when_cancelled() {
+        let (target, bag) = tripods_in_cube();
+        let cancel = AtomicBool::new(false);
+
+        let count = std::thread::scope(|scope|{
+            scope.spawn(||{
+                let mut count = 0;
+                solve_cancellable(&target, bag, &cancel, &mut |_|{
+                    count += 1;
+                    cancel.store(true, Ordering::Relaxed);
+                });
+                count
+            }).join().unwrap()
+        });
+
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn placements_covering_should_report_distinct_placements() {
+        let target = Target::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, 0, 0),
+            Position::new(0, 1, 0),
+            Position::new(1, 1, 0),
+        ));
+        let domino = Template::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, 0, 0),
+        ));
+
+        let placements = target.placements_covering(&Position::new(0, 0, 0), &domino);
+
+        assert_eq!(placements.len(), 2);
+        assert!(placements.iter().all(|piece| piece.contains(&Position::new(0, 0, 0))));
+    }
+
+    #[test]
+    fn placements_should_report_every_distinct_fit() {
+        let target = Target::cuboid(2, 2, 1);
+        let domino = Template::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, 0, 0),
+        ));
+
+        let placements = target.placements(&domino);
+
+        assert_eq!(placements.len(), 4);
+        assert!(placements.iter().all(|piece| target.fits(piece)));
+        assert!(Target::cuboid(1, 1, 1).placements(&domino).is_empty());
+    }
+
+    #[test]
+    fn pruning_should_not_change_solutions() {
+        let (target, bag) = tripods_in_cube();
+
+        for options in &[
+            SolveOptions::default(),
+            SolveOptions { endgame: true, ..SolveOptions::default() },
+            SolveOptions { break_identical_symmetry: true, ..SolveOptions::default() },
+            SolveOptions { check_open_position: true, ..SolveOptions::default() },
+            SolveOptions { forward_check: true, ..SolveOptions::default() },
+        ] {
+            let mut count = 0;
+            solve_with_options(&target, bag.clone(), *options, &mut |_|{ count += 1 });
+            assert_eq!(count, count_solutions(&target, bag.clone()));
+        }
+    }
+
+    #[test]
+    fn forward_check_should_prune_isolated_notch_early() {
+        let mut positions: Vec<Position<(i8, i8, i8)>> = vec!();
+        for x in 0..5 {
+            for y in 0..2 {
+                positions.push(Position::new(x, y, 0));
+            }
+        }
+        positions.push(Position::new(6, 0, 0));
+        let target = Target::new(positions);
+        let bag = Bag::new(vec!(
+            (1,Template::new(vec!(
+                Position::new(0, 0, 0),
+                Position::new(1, 0, 0),
+                Position::new(2, 0, 0),
+                Position::new(3, 0, 0),
+                Position::new(4, 0, 0),
+            ))),
+            (2,Template::new(vec!(
+                Position::new(0, 0, 0),
+                Position::new(1, 0, 0),
+                Position::new(2, 0, 0),
+                Position::new(3, 0, 0),
+                Position::new(3, 1, 0),
+            ))),
+        ));
+
+        let mut count = 0;
+        let unpruned = solve_with_options(&target, bag.clone(), SolveOptions::default(), &mut |_|{ count += 1 });
+        let forward_check = SolveOptions { forward_check: true, ..SolveOptions::default() };
+        let pruned = solve_with_options(&target, bag, forward_check, &mut |_|{ count += 1 });
+
+        assert_eq!(count, 0);
+        assert_eq!(pruned.nodes_visited, 1);
+        assert!(unpruned.nodes_visited > pruned.nodes_visited);
+    }
+
+    #[test]
+    fn endgame_should_find_same_solutions_with_fewer_nodes() {
+        let mut positions: Vec<Position<(i8, i8, i8)>> = vec!();
+        for x in 0..3 {
+            for y in 0..2 {
+                for z in 0..2 {
+                    positions.push(Position::new(x, y, z));
+                }
+            }
+        }
+        let target = Target::new(positions);
+        let bag = Bag::new(vec!(
+            (1,tripod()),
+            (1,Template::new(vec!(
+                Position::new(0, 0, 0),
+                Position::new(1, 0, 0),
+                Position::new(1, 1, 0),
+                Position::new(1, 1, 1),
+            ))),
+            (1,Template::new(vec!(
+                Position::new(0, 0, 0),
+                Position::new(1, 0, 0),
+                Position::new(2, 0, 0),
+                Position::new(1, 1, 0),
+            ))),
+        ));
+
+        let mut plain: Vec<String> = vec!();
+        let plain_stats = solve_with_options(&target, bag.clone(), SolveOptions::default(), &mut |solution|{ plain.push(format!("{}", solution)) });
+        let mut endgame: Vec<String> = vec!();
+        let options = SolveOptions { endgame: true, ..SolveOptions::default() };
+        let endgame_stats = solve_with_options(&target, bag, options, &mut |solution|{ endgame.push(format!("{}", solution)) });
+
+        assert!(!plain.is_empty());
+        assert_eq!(endgame, plain);
+        assert!(endgame_stats.nodes_visited < plain_stats.nodes_visited);
+    }
+
+    #[test]
+    fn identical_symmetry_breaking_should_reduce_search() {
+        let mut positions: Vec<Position<(i8, i8, i8)>> = vec!();
+        for x in 0..2 {
+            for y in 0..2 {
+                for z in 0..3 {
+                    positions.push(Position::new(x, y, z));
+                }
+            }
+        }
+        let target = Target::new(positions);
+        let slab = Template::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, 0, 0),
+            Position::new(0, 1, 0),
+            Position::new(1, 1, 0),
+        ));
+        let bag = Bag::new(vec!(
+            (1, slab.clone()),
+            (1, slab.clone()),
+            (1, slab),
+        ));
+
+        let mut plain: usize = 0;
+        let plain_stats = solve_with_options(&target, bag.clone(), SolveOptions::default(), &mut |_|{ plain += 1 });
+        let mut broken: usize = 0;
+        let options = SolveOptions { break_identical_symmetry: true, ..SolveOptions::default() };
+        let broken_stats = solve_with_options(&target, bag, options, &mut |_|{ broken += 1 });
+
+        assert_eq!(plain, 3 * 2 * 5);
+        assert_eq!(broken, 5);
+        assert!(broken_stats.nodes_visited < plain_stats.nodes_visited);
+    }
+
+    #[test]
+    fn solve_ordered_should_place_pieces_in_order() {
+        let target = Target::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, 0, 0),
+            Position::new(0, 1, 0),
+            Position::new(1, 1, 0),
+        ));
+
+        let bag = Bag::new(vec!(
+            (1,Template::new(vec!(
+                Position::new(0, 0, 0),
+            )).with_name("b")),
+            (1,Template::new(vec!(
+                Position::new(0, 0, 0),
+                Position::new(1, 0, 0),
+                Position::new(0, 1, 0),
+            )).with_name("a")),
+        ));
+
+        let mut solutions: Vec<Solution<(i8, i8, i8)>> = vec!();
+        solve_ordered(&target, bag, &mut |solution|{ solutions.push(solution)});
+        assert_eq!(solutions.len(), 4);
+        for solution in solutions {
+            let names: Vec<Option<&str>> = solution.pieces.iter().map(|piece| piece.name()).collect();
+            assert_eq!(names, vec!(Some("b"), Some("a")));
+        }
+    }
+
+    #[test]
+    fn solutions_should_carry_template_colors() {
+        let target = Target::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, 0, 0),
+            Position::new(0, 1, 0),
+            Position::new(1, 1, 0),
+        ));
+        let red = Rgb::new(255, 0, 0);
+        let blue = Rgb::new(0, 0, 255);
+        let bag = Bag::new(vec!(
+            (1,Template::new(vec!(
+                Position::new(0, 0, 0),
+                Position::new(1, 0, 0),
+            )).with_color(red)),
+            (1,Template::new(vec!(
+                Position::new(0, 0, 0),
+                Position::new(0, 1, 0),
+            )).with_color(blue)),
+        ));
+
+        let mut solutions: Vec<Solution<(i8, i8, i8)>> = vec!();
+        solve(&target, bag, &mut |solution|{ solutions.push(solution)});
+
+        assert_eq!(solutions.len(), 4);
+        for solution in solutions {
+            let mut colors: Vec<Rgb> = solution.pieces.iter().filter_map(|piece| piece.color()).collect();
+            colors.sort_by_key(|color| color.red);
+            assert_eq!(colors, vec!(blue, red));
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn solve_to_ndjson_should_write_a_line_per_solution() {
+        let (target, bag) = tripods_in_cube();
+
+        let mut output: Vec<u8> = vec!();
+        let count = solve_to_ndjson(&target, bag.clone(), &mut output).expect("to write solutions");
+
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(count, count_solutions(&target, bag));
+        assert_eq!(lines.len(), count);
+        for line in lines {
+            let value: serde_json::Value = serde_json::from_str(line).expect("valid json");
+            assert!(value.is_object());
+        }
+    }
+
+    #[test]
+    fn solutions_should_draw_as_grid() {
+        let solution =
+            Solution::empty()
+            .record(
+                &Piece::new(vec!(
+                    Position::d2(0, 0),
+                    Position::d2(1, 0),
+                    Position::d2(2, 0),
+                )))
+            .record(
+                &Piece::new(vec!(
+                    Position::d2(0, 1),
+                    Position::d2(1, 1),
+                )));
+
+        assert_eq!(solution.to_grid(), "AAA\nBB.\n");
+    }
+
+    #[test]
+    fn grids_should_draw_colored_pieces_in_their_color() {
+        let target = Target::rectangle(2, 1);
+        let bag = Bag::new(vec!(
+            (1,Template::new(vec!(
+                Position::d2(0, 0),
+            )).with_color(Rgb::new(255, 0, 0))),
+            (1,Template::new(vec!(
+                Position::d2(0, 0),
+            ))),
+        ));
+        let solution = first_solution(&target, bag).expect("a packing");
+
+        assert_eq!(solution.to_grid(), "\x1b[38;2;255;0;0mA\x1b[0mB\n");
     }
 
     #[test]
@@ -15,12 +15,6 @@ impl VectorDifference<(i8, i8)> for (i8, i8) {
     }
 }
 
-impl VectorDifference<(i8, i8, i8)> for (i8, i8, i8) {
-    fn difference(&self, other: &(i8, i8, i8)) -> (i8, i8, i8) {
-        (other.0 - self.0, other.1 - self.1, other.2 - self.2)
-    }
-}
-
 /// Add a mathematical vector to an other
 pub trait VectorAdd<T> {
     /// self += other
@@ -34,14 +28,55 @@ impl VectorAdd<(i8, i8)> for (i8, i8) {
     }
 }
 
-impl VectorAdd<(i8, i8, i8)> for (i8, i8, i8) {
-    fn add(&mut self, other: &(i8, i8, i8)) {
-        self.0 += other.0;
-        self.1 += other.1;
-        self.2 += other.2;
+/// Determine the component-wise extremes of two mathematical vectors.
+pub trait VectorExtremes<T> {
+    /// Component-wise minimum of self and other.
+    fn minimum(&self, other: &T) -> T;
+    /// Component-wise maximum of self and other.
+    fn maximum(&self, other: &T) -> T;
+}
+
+impl VectorExtremes<(i8, i8)> for (i8, i8) {
+    fn minimum(&self, other: &(i8, i8)) -> (i8, i8) {
+        (self.0.min(other.0), self.1.min(other.1))
+    }
+
+    fn maximum(&self, other: &(i8, i8)) -> (i8, i8) {
+        (self.0.max(other.0), self.1.max(other.1))
     }
 }
 
+/// Implement the vector traits for triples of a signed integer type.
+macro_rules! triple {
+    ($($coordinate:ty),*) => {$(
+        impl VectorDifference<($coordinate, $coordinate, $coordinate)> for ($coordinate, $coordinate, $coordinate) {
+            fn difference(&self, other: &($coordinate, $coordinate, $coordinate)) -> ($coordinate, $coordinate, $coordinate) {
+                (other.0 - self.0, other.1 - self.1, other.2 - self.2)
+            }
+        }
+
+        impl VectorAdd<($coordinate, $coordinate, $coordinate)> for ($coordinate, $coordinate, $coordinate) {
+            fn add(&mut self, other: &($coordinate, $coordinate, $coordinate)) {
+                self.0 += other.0;
+                self.1 += other.1;
+                self.2 += other.2;
+            }
+        }
+
+        impl VectorExtremes<($coordinate, $coordinate, $coordinate)> for ($coordinate, $coordinate, $coordinate) {
+            fn minimum(&self, other: &($coordinate, $coordinate, $coordinate)) -> ($coordinate, $coordinate, $coordinate) {
+                (self.0.min(other.0), self.1.min(other.1), self.2.min(other.2))
+            }
+
+            fn maximum(&self, other: &($coordinate, $coordinate, $coordinate)) -> ($coordinate, $coordinate, $coordinate) {
+                (self.0.max(other.0), self.1.max(other.1), self.2.max(other.2))
+            }
+        }
+    )*}
+}
+
+triple!(i8, i16, i32);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -85,4 +120,23 @@ mod tests {
 
         assert_eq!(a, (5, 6, 7))
     }
+
+    #[test]
+    fn should_determine_extremes_of_triple() {
+        let a: (i8, i8, i8) = (1, 5, 3);
+        let b: (i8, i8, i8) = (4, 2, 3);
+
+        assert_eq!(a.minimum(&b), (1, 2, 3));
+        assert_eq!(a.maximum(&b), (4, 5, 3));
+    }
+
+    #[test]
+    fn should_add_wide_triples() {
+        let mut a: (i16, i16, i16) = (100, 200, 300);
+        let b: (i16, i16, i16) = (100, 100, -400);
+
+        a.add(&b);
+
+        assert_eq!(a, (200, 300, -100))
+    }
 }
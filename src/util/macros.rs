@@ -0,0 +1,67 @@
+//! Macros for writing `Piece`s and `Target`s as literals.
+
+/// Create a `Piece` from a list of coordinates, e.g.
+/// `piece![(0, 0, 0), (1, 0, 0), (1, 1, 0)]`. The coordinates are either
+/// triples or pairs, for a `Piece` in three or two dimensions. An optional
+/// leading name, separated by a semicolon, creates a named `Piece`, e.g.
+/// `piece!["L"; (0, 0), (1, 0), (1, 1)]`.
+#[macro_export]
+macro_rules! piece {
+    ($name:literal; $($position:tt),* $(,)?) => {
+        $crate::puzzle::piece::Piece::named(vec!($($crate::position!($position)),*), $name)
+    };
+    ($($position:tt),* $(,)?) => {
+        $crate::puzzle::piece::Piece::new(vec!($($crate::position!($position)),*))
+    };
+}
+
+/// Create a `Target` from a list of coordinates, e.g.
+/// `target![(0, 0, 0), (1, 0, 0), (1, 1, 0)]`. The coordinates are either
+/// triples or pairs, for a `Target` in three or two dimensions.
+#[macro_export]
+macro_rules! target {
+    ($($position:tt),* $(,)?) => {
+        $crate::puzzle::solver::Target::new(vec!($($crate::position!($position)),*))
+    };
+}
+
+/// Create a `Position` from a triple or a pair of coordinates. Used by the
+/// `piece!` and `target!` macros.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! position {
+    (($x:expr, $y:expr, $z:expr)) => {
+        $crate::puzzle::piece::Position::new($x, $y, $z)
+    };
+    (($x:expr, $y:expr)) => {
+        $crate::puzzle::piece::Position::d2($x, $y)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::puzzle::piece::{Position, Piece};
+    use super::super::super::puzzle::solver::Target;
+
+    #[test]
+    fn piece_should_equal_explicit_construction() {
+        assert_eq!(crate::piece![(0, 0, 0), (1, 0, 0), (1, 1, 0)], Piece::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, 0, 0),
+            Position::new(1, 1, 0),
+        )));
+        assert_eq!(crate::piece!["L"; (0, 0), (1, 0), (1, 1),], Piece::named(vec!(
+            Position::d2(0, 0),
+            Position::d2(1, 0),
+            Position::d2(1, 1),
+        ), "L"));
+    }
+
+    #[test]
+    fn target_should_equal_explicit_construction() {
+        assert_eq!(crate::target![(1, 1, 0), (0, 0, 0)], Target::new(vec!(
+            Position::new(0, 0, 0),
+            Position::new(1, 1, 0),
+        )));
+    }
+}
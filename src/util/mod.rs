@@ -1,3 +1,4 @@
 //! Various utilities for easily describing packing problems.
 
 pub mod target;
+pub mod macros;
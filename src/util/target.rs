@@ -1,19 +1,16 @@
 //! Utilities for creating `Target`s.
 
-use super::super::puzzle::solver::Target;
+use std::collections::BTreeSet;
+
+use super::super::puzzle::solver::{DimensionError, Target};
 use super::super::puzzle::piece::Position;
 
-/// Create a brick `Target`, defined by the dimensions.
+/// Create a brick `Target`, defined by the dimensions. *Note* this panics when
+/// a dimension is larger than 128, because the coordinates of the brick would
+/// not fit in an `i8`. See `Target::try_from_box`.
 pub fn brick(width: u8, height: u8, depth: u8) -> Target<(i8, i8, i8)> {
-    let mut positions: Vec<Position<(i8, i8, i8)>> = vec!();
-    for x in 0..width {
-        for y in 0..height {
-            for z in 0..depth {
-                positions.push(Position::new(x as i8, y as i8, z as i8));
-            }
-        }
-    }
-    Target::new(positions)
+    Target::try_from_box(usize::from(width), usize::from(height), usize::from(depth))
+        .expect("dimensions of the brick to fit in an i8")
 }
 
 /// Create a rectangle `Target`, defined by the dimensions.
@@ -27,6 +24,74 @@ pub fn rectangle(width: u8, height: u8) -> Target<(i8, i8)> {
     Target::new(positions)
 }
 
+/// Compose a `Target` from boxes and cells that are added or removed in turn.
+/// Every cell is part of the `Target` at most once.
+#[derive(Debug, Clone, Default)]
+pub struct TargetBuilder {
+    positions: BTreeSet<Position<(i8, i8, i8)>>,
+}
+
+impl TargetBuilder {
+    /// Start composing a `Target` without cells.
+    pub fn new() -> TargetBuilder {
+        TargetBuilder { positions: BTreeSet::new() }
+    }
+
+    /// Add the cells of the box with its minimum corner at `corner` and with
+    /// the given `(width, height, depth)`. Fails, like `Target::try_from_box`
+    /// does, when cells of the box would lie beyond the range of the
+    /// coordinates.
+    pub fn add_box(mut self, corner: (i8, i8, i8), dimensions: (i8, i8, i8)) -> Result<TargetBuilder, DimensionError> {
+        let (x, y, z) = corner;
+        let (width, height, depth) = dimensions;
+        for (start, size) in &[(x, width), (y, height), (z, depth)] {
+            if *size > 0 && start.checked_add(size - 1).is_none() {
+                return Err(DimensionError::TooLarge(*size as usize));
+            }
+        }
+        self.positions.extend(box_positions(corner, dimensions));
+        Ok(self)
+    }
+
+    /// Remove the cells of the box with its minimum corner at `corner` and with
+    /// the given `(width, height, depth)`.
+    pub fn remove_box(mut self, corner: (i8, i8, i8), dimensions: (i8, i8, i8)) -> TargetBuilder {
+        for position in box_positions(corner, dimensions) {
+            self.positions.remove(&position);
+        }
+        self
+    }
+
+    /// Add the cell at the given coordinates.
+    pub fn add_cell(mut self, x: i8, y: i8, z: i8) -> TargetBuilder {
+        self.positions.insert(Position::new(x, y, z));
+        self
+    }
+
+    /// Remove the cell at the given coordinates.
+    pub fn remove_cell(mut self, x: i8, y: i8, z: i8) -> TargetBuilder {
+        self.positions.remove(&Position::new(x, y, z));
+        self
+    }
+
+    /// Create the `Target`, with its cells in sorted order.
+    pub fn build(self) -> Target<(i8, i8, i8)> {
+        self.positions.into_iter().collect()
+    }
+}
+
+/// The cells of the box that lie within the range of the coordinates.
+fn box_positions(corner: (i8, i8, i8), dimensions: (i8, i8, i8)) -> impl Iterator<Item = Position<(i8, i8, i8)>> {
+    let (x, y, z) = corner;
+    let (width, height, depth) = dimensions;
+    (0..width)
+        .filter_map(move |dx| x.checked_add(dx))
+        .flat_map(move |x| (0..height)
+            .filter_map(move |dy| y.checked_add(dy))
+            .flat_map(move |y| (0..depth)
+                .filter_map(move |dz| z.checked_add(dz))
+                .map(move |z| Position::new(x, y, z))))
+}
 
 #[cfg(test)]
 mod tests {
@@ -45,6 +110,41 @@ mod tests {
 		    assert!(target.fits(&Piece::new(vec!(Position::new(0,1,2)))));
 	  }
 
+	  #[test]
+	  fn builder_should_compose_an_l_shape() {
+		    let target = TargetBuilder::new()
+		        .add_box((0, 0, 0), (3, 2, 1))
+		        .expect("a box within range")
+		        .remove_box((1, 1, 0), (2, 1, 1))
+		        .add_cell(0, 2, 0)
+		        .add_cell(0, 0, 0)
+		        .build();
+
+		    assert_eq!(target.positions(), &[
+		        Position::new(0, 0, 0),
+		        Position::new(0, 1, 0),
+		        Position::new(0, 2, 0),
+		        Position::new(1, 0, 0),
+		        Position::new(2, 0, 0),
+		    ]);
+	  }
+
+	  #[test]
+	  fn builder_should_refuse_boxes_beyond_the_range() {
+		    assert_eq!(TargetBuilder::new().add_box((126, 0, 0), (3, 1, 1)).err(), Some(DimensionError::TooLarge(3)));
+		    assert_eq!(TargetBuilder::new().add_box((0, 0, -128), (1, 1, 0)).map(TargetBuilder::build), Ok(Target::new(vec!())));
+
+		    let target = TargetBuilder::new()
+		        .add_box((126, 0, 0), (2, 1, 1))
+		        .expect("a box within range")
+		        .build();
+
+		    assert_eq!(target.positions(), &[
+		        Position::new(126, 0, 0),
+		        Position::new(127, 0, 0),
+		    ]);
+	  }
+
 	  #[test]
 	  fn should_create_a_rectangle() {
 		    let target = rectangle(2,2);